@@ -1,4 +1,10 @@
 #![no_std]
+// Events keep the v2.x `(topic,) -> data` layout that off-chain consumers parse,
+// so the contract still publishes through the legacy `Events::publish` API.
+#![allow(deprecated)]
+// The args builders and test client that `#[contractimpl]` generates for the
+// widest entry points don't carry their per-function `too_many_arguments` allows.
+#![allow(clippy::too_many_arguments)]
 //! # ZKP Balance Verifier Contract
 //! 
 //! Production-ready Soroban smart contract for verifying Zero-Knowledge Proofs (ZKP)
@@ -12,8 +18,125 @@
 //! - **Bulletproofs (secp256k1)**: True zero-knowledge range proofs with Pedersen
 //!   commitments. Structural validation and Fiat-Shamir binding checked on-chain;
 //!   full EC verification performed off-chain (Soroban lacks native secp256k1 ops).
+//!
+//! ## Events
+//! A minimal `verification_result` event is always published, and every
+//! successful verification also publishes a typed `ProofVerified` event for
+//! indexers (topics `("proof_verified", kind)`). Debug events such as
+//! `verify_attempt` (input lengths only) are published when the admin has enabled
+//! them via `set_debug_events`; they are off by default. No event carries raw
+//! proof bytes.
+//!
+//! ## Deployment Domain
+//! `initialize` takes an immutable `domain` that is folded into every HMAC message
+//! as a `len(domain) || domain` prefix (u32 big-endian length), so proofs made for
+//! one deployment (e.g. testnet) never verify on another (e.g. mainnet). With an
+//! empty or unset domain the prefix is omitted and messages keep the plain layouts
+//! documented on each verifier (and used by `test_vectors`).
+//!
+//! ## Namespaces
+//! Several independent verifiers can share one deployment. Each `namespace` id
+//! (see `init_namespace`) has its own admin, HMAC key, domain, asset decimals,
+//! thresholds, counters and replay records, all stored under a
+//! `DataKey::Namespaced(namespace, ..)` key, so a proof or configuration change in
+//! one namespace never affects another. The namespaced entry points run the same
+//! verification code as their contract-wide counterparts, with the namespace
+//! selecting which key, domain and configuration the shared helpers read.
+
+pub mod test_vectors;
+
+use core::cmp::Ordering;
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, panic_with_error,
+    xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, InvokeError, Map, Symbol, Val, Vec,
+};
+
+/// Minimum salt length (bytes) accepted by the HMAC verifiers.
+const MIN_SALT_LEN: u32 = 16;
+
+/// Maximum data length (bytes) accepted by the HMAC verifiers. HMAC cost scales
+/// with input size, so oversized data is rejected before hashing.
+pub const MAX_DATA_LEN: u32 = 4096;
+
+/// Minimum number of ledgers that must close between `commit_balance` and
+/// `reveal_balance`, so a commitment can't be opened in the same transaction.
+pub const MIN_REVEAL_GAP: u32 = 5;
+
+/// Maximum rate-limited verifications per submitter within one window.
+pub const RATE_LIMIT_MAX_CALLS: u32 = 10;
+
+/// Length in ledgers of a rate-limit window.
+pub const RATE_LIMIT_WINDOW: u32 = 100;
+
+/// Maximum items in one batch or aggregate until the admin sets a limit.
+pub const MAX_ITEMS: u32 = 100;
+
+/// Maximum summed data length of one batch or aggregate until the admin sets a limit.
+pub const MAX_TOTAL_BYTES: u32 = 65_536;
+
+/// Index `verify_batch_first_failure` reports for a malformed batch (input
+/// vectors of different lengths, or over `batch_limits`); no real item has it.
+pub const INVALID_BATCH_INDEX: u32 = u32::MAX;
+
+/// Ledgers a positive `verify_proof_cached` result stays cached.
+pub const VERIFY_CACHE_TTL: u32 = 100;
+
+/// Token-bucket capacity (the largest burst) until the admin sets one.
+pub const DEFAULT_BUCKET_CAPACITY: u32 = 10;
 
-use soroban_sdk::{contract, contractimpl, Bytes, BytesN, Env, Symbol, Vec};
+/// Tokens added to a bucket per ledger until the admin sets a rate.
+pub const DEFAULT_BUCKET_REFILL_PER_LEDGER: u32 = 1;
+
+/// Number of ledgers (about one day) during which a consumed proof is rejected
+/// by `verify_proof_once`. After that its replay record may be swept.
+pub const REPLAY_WINDOW: u32 = 17_280;
+
+/// Maximum PBKDF2 iteration count accepted by `derive_key_pbkdf2`. Each iteration
+/// is one HMAC-SHA256 (two SHA-256 compressions over ~100 bytes), so the cap
+/// bounds the derivation at roughly 2,000 host SHA-256 calls.
+pub const MAX_PBKDF2_ITERATIONS: u32 = 1_000;
+
+/// SHA-256 invocations in one HMAC-SHA256 with a key of at most one block (every
+/// 32-byte key): the inner and the outer hash.
+pub const SHA256_PER_HMAC: u32 = 2;
+
+/// Header version accepted by `verify_proof_tagged`.
+pub const TAGGED_PROOF_VERSION: u8 = 1;
+
+/// Length of the `verify_proof_tagged` header: version, algorithm, two reserved bytes.
+pub const TAGGED_PROOF_HEADER_LEN: u32 = 4;
+
+/// Length of a `verify_committed_sum` commitment: 16-byte masked value, 32-byte tag.
+pub const COMMITMENT_LEN: u32 = 48;
+
+/// Allowed-algorithms mask used until the admin sets one: every `HashAlg`.
+pub const ALL_HASH_ALGS: u32 = (1 << HashAlg::Sha256 as u32)
+    | (1 << HashAlg::Sha512 as u32)
+    | (1 << HashAlg::Keccak256 as u32);
+
+/// Domain tag prefixed to the inner message of nested (NMAC-style) proofs so they
+/// can never be confused with single-HMAC proofs over the same data.
+const NESTED_HMAC_DOMAIN: &[u8] = b"Tessera_HMAC_Nested_v1";
+
+/// Known-answer vector checked by `self_test`: RFC 4231 test case 2.
+const SELF_TEST_KEY: &[u8] = b"Jefe";
+const SELF_TEST_MESSAGE: &[u8] = b"what do ya want for nothing?";
+const SELF_TEST_HMAC: [u8; 32] = [
+    0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75,
+    0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec,
+    0x38, 0x43,
+];
+
+/// Known-answer vector checked by `self_test` through `verify_proof`'s message
+/// layout: `test_vectors::BALANCE_SALT_16` under this domain, i.e.
+/// `HMAC-SHA256(KEY, len(domain) || domain || data || salt)`.
+const SELF_TEST_DOMAIN: &[u8] = b"Tessera_Self_Test_v1";
+const SELF_TEST_PROOF: [u8; 32] = [
+    0x24, 0x43, 0x1b, 0x94, 0xee, 0xfa, 0xa3, 0xf5, 0xf0, 0x14, 0x4d, 0x3d, 0x7a, 0x17, 0xbe,
+    0x94, 0x75, 0x05, 0x77, 0xca, 0xa7, 0xdd, 0x63, 0x22, 0x6b, 0x19, 0x3e, 0x61, 0x31, 0x43,
+    0xa6, 0x0e,
+];
 
 /// Contract for verifying ZKP-based proofs using HMAC-SHA256.
 /// 
@@ -23,9 +146,12 @@ use soroban_sdk::{contract, contractimpl, Bytes, BytesN, Env, Symbol, Vec};
 pub struct ZkpVerifier;
 
 /// Error codes for proof verification failures
+#[contracttype]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum VerificationError {
+    /// No error; the verification succeeded
+    None = 0,
     /// The provided proof does not match the computed hash
     InvalidProof = 1,
     /// The input data format is invalid
@@ -38,254 +164,4495 @@ pub enum VerificationError {
     InvalidCommitment = 5,
     /// Range bounds are invalid
     InvalidRange = 6,
+    /// The data exceeds `MAX_DATA_LEN`
+    DataTooLarge = 7,
+    /// No balance commitment is stored for the account
+    MissingCommitment = 8,
+    /// The reveal came before `MIN_REVEAL_GAP` ledgers had elapsed
+    RevealTooEarly = 9,
+    /// The revealed balance and blinding do not open the stored commitment
+    CommitmentMismatch = 10,
+    /// The ledger embedded in the proof is below the required minimum
+    ProofBackdated = 11,
+    /// The hash algorithm is not in the admin-configured allowed set
+    DisallowedAlgorithm = 12,
+    /// The submitter exceeded its rate limit (window counter or token bucket)
+    RateLimited = 13,
+    /// A guarded cross-contract invocation was re-entered
+    ReentrantCall = 14,
+    /// No decimals are configured for the asset
+    UnknownAsset = 15,
+    /// The proof was already consumed within its replay window
+    ProofReplayed = 16,
+    /// The balance field was empty (a zero balance must be sent as e.g. `"0"`)
+    MissingBalance = 17,
+    /// A validity window with `not_before > expires_at`
+    InvalidWindow = 18,
+    /// The proof's validity window has not opened yet
+    ProofNotYetValid = 19,
+    /// The proof's validity window has closed
+    ProofExpired = 20,
+    /// A tagged proof header with an unknown version, algorithm or non-zero reserved bytes
+    UnknownProofHeader = 21,
+    /// The proven member is not in the on-chain membership set
+    NotAMember = 22,
+    /// The supplied key does not hash to the stored key commitment
+    KeyCommitmentMismatch = 23,
+    /// No required threshold is configured for the asset
+    MissingThreshold = 24,
+    /// Two batch items share the same salt
+    DuplicateSalt = 25,
+    /// No data is stored under the referenced key
+    MissingData = 26,
+    /// The claim id has already been claimed
+    AlreadyClaimed = 27,
+    /// A batch has more than `max_items` items or `max_total_bytes` of data
+    BatchTooLarge = 28,
+    /// The token contract's `balance()` call failed or returned a non-`i128`
+    BalanceFetchFailed = 29,
+    /// A sequenced proof's number is not the stream's next expected number
+    SequenceMismatch = 30,
+    /// An integer-only amount contains a decimal point
+    UnexpectedDecimal = 31,
+    /// A stored ledger sequence is ahead of the current ledger
+    LedgerInFuture = 32,
+    /// The key provider had no key for the id, or its `get_key()` call failed
+    KeyUnavailable = 33,
+    /// A Merkle path doesn't lead from the leaf to the authenticated root
+    MerkleMismatch = 34,
+    /// Data isn't in the required canonical encoding
+    InvalidEncoding = 35,
+    /// The salt was never registered with `register_salt`
+    SaltNotRegistered = 36,
+    /// The registered salt has already been used by a successful verification
+    SaltConsumed = 37,
+    /// A record doesn't match its schema: wrong segment count, or a segment
+    /// empty or over its maximum length
+    SchemaMismatch = 38,
 }
 
-#[contractimpl]
-impl ZkpVerifier {
-    /// Verifies a Zero-Knowledge Proof using HMAC-SHA256.
-    /// 
-    /// This function computes HMAC-SHA256(data + salt) using the provided HMAC key
-    /// and compares it with the provided proof in constant time to prevent timing attacks.
-    /// 
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
-    /// * `data` - The original data that was proven
-    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
-    /// * `hmac_key` - The HMAC secret key (32 bytes)
-    /// 
-    /// # Returns
-    /// * `true` if the proof is valid
-    /// * `false` if the proof is invalid
-    /// 
-    /// # Examples
-    /// ```
-    /// // Verify a balance proof
-    /// let is_valid = contract.verify_proof(&env, proof, data, salt, hmac_key);
-    /// ```
-    pub fn verify_proof(
-        env: Env,
-        proof: BytesN<32>,
-        data: Bytes,
-        salt: Bytes,
-        hmac_key: BytesN<32>,
-    ) -> bool {
-        // Log verification attempt
-        env.events().publish(
-            (Symbol::new(&env, "verify_attempt"),),
-            (data.len(), salt.len()),
-        );
+/// Errors that abort the invocation (as opposed to `VerificationError`, which is
+/// reported through events and return values).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize` (or `init_namespace`) was called on an already initialized
+    /// contract (or namespace)
+    AlreadyInitialized = 1,
+    /// The contract (or a key commitment or namespace) has not been initialized
+    NotInitialized = 2,
+    /// A debug-only method was called while debug events are disabled
+    DebugDisabled = 3,
+    /// A PBKDF2 iteration count of zero or above `MAX_PBKDF2_ITERATIONS`
+    InvalidIterations = 4,
+    /// `accept_admin` was called with no admin transfer pending
+    NoPendingAdmin = 5,
+}
 
-        // Validate input lengths
-        if salt.len() < 16 {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidSaltLength as u32,
-            );
-            return false;
+/// Hash function underlying an HMAC proof.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum HashAlg {
+    /// HMAC-SHA256 (32-byte MAC), computed with the Soroban host function
+    Sha256 = 0,
+    /// HMAC-SHA512 (64-byte MAC), computed in-contract
+    Sha512 = 1,
+    /// HMAC-Keccak256 (32-byte MAC, 136-byte block) for EVM interop, computed with
+    /// the Soroban host function
+    Keccak256 = 2,
+}
+
+impl HashAlg {
+    /// Bit representing this algorithm in the allowed-algorithms mask.
+    pub fn mask_bit(self) -> u32 {
+        1 << (self as u32)
+    }
+
+    /// Returns the algorithm with the given discriminant, if any.
+    pub fn from_u32(value: u32) -> Option<HashAlg> {
+        match value {
+            0 => Some(HashAlg::Sha256),
+            1 => Some(HashAlg::Sha512),
+            2 => Some(HashAlg::Keccak256),
+            _ => None,
         }
+    }
 
-        // Concatenate data and salt
-        let mut message = Bytes::new(&env);
-        message.append(&data);
-        message.append(&salt);
+    /// Length in bytes of the MAC produced with this algorithm.
+    pub fn mac_len(self) -> u32 {
+        match self {
+            HashAlg::Sha256 | HashAlg::Keccak256 => 32,
+            HashAlg::Sha512 => 64,
+        }
+    }
+}
 
-        // Compute HMAC-SHA256
-        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+/// Comparison applied between a proven value and a required value.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum CompareOp {
+    /// value >= required
+    Gte = 0,
+    /// value > required
+    Gt = 1,
+    /// value <= required
+    Lte = 2,
+    /// value < required
+    Lt = 3,
+    /// value == required
+    Eq = 4,
+}
 
-        // Constant-time comparison to prevent timing attacks
-        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+/// MAC construction used by `verify_proof_mode`.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MacMode {
+    /// Standard HMAC-SHA256, as used by `verify_proof`
+    Hmac = 0,
+    /// `SHA-256(key || message)`. Weaker than HMAC: vulnerable to length
+    /// extension. Only for verifying proofs from partner systems that use it.
+    PrefixMac = 1,
+}
 
-        // Log result
-        env.events().publish(
-            (Symbol::new(&env, "verification_result"),),
-            is_valid,
-        );
+/// Outcome of `verify_proof_meta`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationResult {
+    /// Whether the proof verified
+    pub valid: bool,
+    /// Why verification failed, or `VerificationError::None` on success
+    pub reason: VerificationError,
+    /// Ledger sequence at which the proof was checked
+    pub ledger: u32,
+}
 
-        is_valid
+/// Formatting accepted by the fixed-point parser beyond plain `[-]digits[.digits]`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct NumberFormat {
+    /// Accept `,` thousands separators in the integer part (groups of three)
+    allow_separators: bool,
+    /// Decimal separator: `.` (default) or `,`
+    decimal_sep: u8,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            allow_separators: false,
+            decimal_sep: b'.',
+        }
     }
+}
 
-    /// Verifies a balance proof with additional balance check.
-    /// 
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    /// * `proof` - The proof hash to verify
-    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
-    /// * `required_amount_data` - The required amount as bytes (decimal string, e.g., "500.25")
-    /// * `salt` - The cryptographic salt
-    /// * `hmac_key` - The HMAC secret key
-    /// 
-    /// # Returns
-    /// * `true` if proof is valid and balance >= required_amount
-    pub fn verify_balance_proof(
-        env: Env,
-        proof: BytesN<32>,
-        balance_data: Bytes,
-        required_amount_data: Bytes,
-        salt: Bytes,
-        hmac_key: BytesN<32>,
-    ) -> bool {
-        // First verify the proof itself
-        let proof_valid = Self::verify_proof(
-            env.clone(),
-            proof,
-            balance_data.clone(),
-            salt,
-            hmac_key,
-        );
+/// A multi-field attestation proven as a single statement by `verify_statement`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Statement {
+    /// The account the statement is about
+    pub account: Address,
+    /// Asset identifier (see `set_asset_decimals`)
+    pub asset: BytesN<32>,
+    /// The balance as a decimal string (e.g., "1000.50")
+    pub balance: Bytes,
+    /// Unix timestamp at which the statement was made
+    pub timestamp: u64,
+}
 
-        if !proof_valid {
-            return false;
-        }
+/// Shape of a structured record checked by `verify_schema_proof`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Schema {
+    /// Maximum length of each segment, in order; a record must have exactly this
+    /// many segments
+    pub max_lengths: Vec<u32>,
+}
 
-        // Parse and compare numeric values
-        let balance = Self::parse_decimal_to_scaled(&balance_data);
-        let required = Self::parse_decimal_to_scaled(&required_amount_data);
+/// A balance commitment awaiting its reveal.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceCommitment {
+    /// `sha256(balance_data || blinding)`
+    pub commitment: BytesN<32>,
+    /// Ledger sequence at which the commitment was stored
+    pub ledger: u32,
+}
 
-        let balance_sufficient = match (balance, required) {
-            (Some(b), Some(r)) => b >= r,
-            _ => {
-                env.events().publish(
-                    (Symbol::new(&env, "error"),),
-                    VerificationError::InvalidInput as u32,
-                );
-                false
-            }
-        };
+/// Rate-limit counter for a submitter.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateWindow {
+    /// Verifications performed in the current window
+    pub count: u32,
+    /// Ledger sequence at which the current window started
+    pub window_start: u32,
+}
 
-        env.events().publish(
-            (Symbol::new(&env, "balance_check"),),
-            balance_sufficient,
-        );
+/// Per-namespace state, stored under `DataKey::Namespaced`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NamespaceKey {
+    /// Address allowed to configure the namespace
+    Admin,
+    /// HMAC key registered for the namespace
+    HmacKey,
+    /// Domain folded into the namespace's HMAC messages
+    Domain,
+    /// Number of proofs verified successfully in the namespace
+    VerifiedCount,
+    /// Decimal precision configured for an asset within the namespace
+    AssetDecimals(BytesN<32>),
+    /// Minimum balance for an asset within the namespace
+    RequiredThreshold(BytesN<32>),
+    /// Replay record for a proof consumed in the namespace, keyed by proof id
+    ConsumedProof(BytesN<32>),
+}
 
-        proof_valid && balance_sufficient
-    }
+/// Previous HMAC key kept by `begin_rotation` for a grace period.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyRotation {
+    /// The key that was current before the rotation
+    pub previous_key: BytesN<32>,
+    /// First ledger at which the previous key is no longer accepted
+    pub expires_at: u32,
+}
 
-    /// Parses a decimal string (e.g., "1234.56") to a scaled integer for comparison.
-    /// Returns None if parsing fails or if no digits are present.
-    /// The result is scaled by 10^8 to handle up to 8 decimal places.
-    fn parse_decimal_to_scaled(data: &Bytes) -> Option<i128> {
-        // Return None for empty input
-        if data.len() == 0 {
-            return None;
-        }
+/// Token-bucket state for a submitter.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenBucket {
+    /// Tokens available as of `last_refill`
+    pub tokens: u32,
+    /// Ledger sequence the bucket was last refilled at
+    pub last_refill: u32,
+}
 
-        let mut result: i128 = 0;
-        let mut decimal_places: u32 = 0;
-        let mut found_decimal = false;
-        let mut is_negative = false;
-        let mut has_digits = false; // Track if at least one digit was parsed
+/// Storage keys. Configuration lives in instance storage; per-account state in
+/// persistent storage.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    /// Address allowed to change contract configuration
+    Admin,
+    /// HMAC key registered for this deployment (readable by anyone from ledger state)
+    HmacKey,
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin`
+    PendingAdmin,
+    /// Deployment domain set at initialization
+    Domain,
+    /// Per-`VerificationError` failure counters
+    ErrorStats,
+    /// Whether debug events carrying raw proof material are published
+    DebugEvents,
+    /// Pending balance commitment for an account
+    BalanceCommitment(Address),
+    /// Bitmask of `HashAlg` values accepted by the algorithm-parameterized verifiers
+    AllowedAlgs,
+    /// Rate-limit window for a submitter
+    RateLimit(Address),
+    /// Token bucket for a submitter
+    TokenBucket(Address),
+    /// Token-bucket capacity
+    BucketCapacity,
+    /// Tokens added to each bucket per ledger
+    BucketRefillPerLedger,
+    /// Maximum items in one batch or aggregate
+    MaxItems,
+    /// Maximum summed data length of one batch or aggregate
+    MaxTotalBytes,
+    /// Whether the batch verifiers reject batches in which two items share a salt
+    UniqueSalts,
+    /// Cached positive `verify_proof_cached` result (temporary storage)
+    VerifyCache(BytesN<32>),
+    /// Next sequence number `verify_sequenced` accepts for a stream
+    NextSeq(BytesN<32>),
+    /// Previous HMAC key still accepted during a key rotation
+    KeyRotation,
+    /// State of one logical verifier sharing this contract (persistent storage)
+    Namespaced(BytesN<32>, NamespaceKey),
+    /// Set while `verify_then_invoke` is calling out to another contract
+    InvokeInProgress,
+    /// Decimal precision configured for an asset
+    AssetDecimals(BytesN<32>),
+    /// Replay record for a consumed proof, keyed by proof id; holds the ledger
+    /// from which the record may be evicted
+    ConsumedProof(BytesN<32>),
+    /// Presence marks a member of the on-chain membership set
+    Member(BytesN<32>),
+    /// `sha256(key)` of the key `verify_with_committed_key` accepts
+    KeyCommitment,
+    /// Minimum balance (scaled by the asset's decimals) for an asset
+    RequiredThreshold(BytesN<32>),
+    /// Length and head hash of the audit log (persistent storage)
+    AuditLog,
+    /// Audit log entry at an index (persistent storage)
+    AuditEntry(u32),
+    /// Data stored by `store_data` for `verify_stored_data_proof`
+    StoredData(BytesN<32>),
+    /// Presence marks a claim id consumed by `claim_once`
+    Claimed(BytesN<32>),
+    /// Salt issued through `register_salt`, keyed by `sha256(salt)`; holds
+    /// whether it has been consumed
+    RegisteredSalt(BytesN<32>),
+}
 
-        for i in 0..data.len() {
-            let byte = data.get(i)?;
-            
-            // Handle negative sign (only at the start, before any digits)
-            if byte == b'-' && !has_digits && !found_decimal {
-                is_negative = true;
-                continue;
-            }
-            
-            // Handle decimal point
-            if byte == b'.' {
-                if found_decimal {
-                    return None; // Multiple decimal points
-                }
-                found_decimal = true;
-                continue;
-            }
+/// Published whenever a proof authenticates, on every verification entry point.
+///
+/// `kind` names what the proof attests: `data` for plain `verify_proof`-layout
+/// proofs, `balance` for the `verify_balance_proof` layout, and `asset_balance`,
+/// `live_balance`, `aggregate`, `statement`, `schema`, `claim`, `membership`,
+/// `merkle_root`, `kyc_tier`, `nested`, `sequenced`, `context`, `contract_bound`,
+/// `min_ledger` or `time_window` for the entry points with their own layouts.
+/// Structural checks that authenticate nothing (`verify_zk_range_proof`) never
+/// publish it.
+/// For entry points that check more after authentication (a threshold, set
+/// membership), the event records the authenticated proof; the overall outcome
+/// is the call's return value and its own result event.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofVerified {
+    /// What the proof attests
+    #[topic]
+    pub kind: Symbol,
+    /// `sha256(proof)`, as in the audit log
+    pub proof_id: BytesN<32>,
+    /// Ledger sequence the proof was verified at
+    pub ledger: u32,
+}
 
-            // Handle digits
-            if byte >= b'0' && byte <= b'9' {
-                has_digits = true;
-                let digit = (byte - b'0') as i128;
-                result = result.checked_mul(10)?.checked_add(digit)?;
-                
-                if found_decimal {
-                    decimal_places += 1;
-                    if decimal_places > 8 {
-                        // Too many decimal places, stop processing
-                        break;
-                    }
-                }
-            } else if byte != b' ' {
-                // Invalid character (allow spaces to be ignored)
-                return None;
-            }
-        }
+/// One verification outcome in the audit log.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditEntry {
+    /// `sha256(proof)`, matching `proof_id` for 32-byte proofs
+    pub proof_id: BytesN<32>,
+    /// Whether the proof verified
+    pub is_valid: bool,
+    /// Ledger sequence the verification ran at
+    pub ledger: u32,
+}
 
-        // Must have at least one digit to be valid
-        if !has_digits {
-            return None;
-        }
+/// Published for every audit log entry, alongside the copy kept in persistent
+/// storage (see `log_entry`), so indexers can follow the log without reading
+/// contract state.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditLogged {
+    /// Position of the entry in the log
+    #[topic]
+    pub index: u32,
+    /// `sha256(proof)`, matching `proof_id` for 32-byte proofs
+    pub proof_id: BytesN<32>,
+    /// Whether the proof verified
+    pub is_valid: bool,
+    /// Ledger sequence the verification ran at
+    pub ledger: u32,
+}
 
-        // Scale to 8 decimal places for consistent comparison
-        const SCALE_FACTOR: u32 = 8;
-        let scale_needed = SCALE_FACTOR.saturating_sub(decimal_places);
-        
-        for _ in 0..scale_needed {
-            result = result.checked_mul(10)?;
-        }
+/// Chains an audit entry onto `prev`:
+/// `sha256(prev || proof_id || is_valid (1 byte) || ledger (u32 big-endian))`.
+/// The log starts from an all-zero head, so replaying every entry from index 0
+/// through this function reproduces `log_head`.
+pub fn audit_chain_hash(env: &Env, prev: &BytesN<32>, entry: &AuditEntry) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &prev.to_array());
+    preimage.append(&entry.proof_id.clone().into());
+    preimage.push_back(entry.is_valid as u8);
+    preimage.extend_from_array(&entry.ledger.to_be_bytes());
+    env.crypto().sha256(&preimage).into()
+}
 
-        if is_negative {
-            result = -result;
-        }
+/// A keyed MAC over a message, producing a 32-byte tag.
+///
+/// `verify_proof` checks proofs through `HmacSha256`; other schemes can be
+/// plugged into the generic verification path (`verify_proof_with`) by
+/// implementing this trait.
+pub trait Mac {
+    /// Computes the MAC of `message` under `key`.
+    fn compute(env: &Env, key: &BytesN<32>, message: &Bytes) -> BytesN<32>;
+}
 
-        Some(result)
+/// HMAC-SHA256 (RFC 2104), the MAC all 32-byte proofs use by default.
+pub struct HmacSha256;
+
+impl Mac for HmacSha256 {
+    fn compute(env: &Env, key: &BytesN<32>, message: &Bytes) -> BytesN<32> {
+        ZkpVerifier::compute_hmac(env, message, key)
     }
+}
 
-    /// Batch verification of multiple proofs for efficiency.
-    /// 
+/// Orders two 32-byte values as unsigned big-endian integers: byte by byte from
+/// the first, with each byte unsigned (`0x80` sorts above `0x7f`). Ordering
+/// checks over 32-byte values go through this helper so they agree on the order;
+/// `verify_batch_ordered` applies the same unsigned byte order to its
+/// variable-length items.
+pub fn compare_bytes(a: &BytesN<32>, b: &BytesN<32>) -> Ordering {
+    a.to_array().cmp(&b.to_array())
+}
+
+/// Returns the number of ledgers from `past` to `current`, or `None` if `past`
+/// is ahead of `current`. Freshness, rate-limit and commit-reveal checks go
+/// through this helper and treat `None` as an error, so an out-of-order ledger
+/// can't wrap around into a huge (or zero) gap.
+pub fn ledger_gap(current: u32, past: u32) -> Option<u32> {
+    current.checked_sub(past)
+}
+
+#[contractimpl]
+impl ZkpVerifier {
+    /// Sets the contract admin and registers the deployment's HMAC key and domain.
+    ///
+    /// Can only be called once; a second call panics with
+    /// `ContractError::AlreadyInitialized` so the admin, key and domain can't be
+    /// overwritten. Until it runs anyone may call it, so invoke it right after
+    /// deploying and check `admin()` before relying on the deployment.
+    ///
+    /// The key is kept in instance storage, which anyone can read from ledger
+    /// state: it only authenticates `verify_with_stored_key` proofs against
+    /// parties that don't read the chain. Prefer passing the key per call, or
+    /// `verify_with_provider`, when proofs must be unforgeable by observers.
+    ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `proofs` - Vector of proofs to verify
-    /// * `data_items` - Vector of data items corresponding to each proof
-    /// * `salts` - Vector of salts corresponding to each proof
-    /// * `hmac_key` - The HMAC secret key (same for all proofs)
-    /// 
-    /// # Returns
-    /// * `true` if ALL proofs are valid, `false` if any proof is invalid
-    pub fn verify_batch(
-        env: Env,
-        proofs: Vec<BytesN<32>>,
-        data_items: Vec<Bytes>,
-        salts: Vec<Bytes>,
-        hmac_key: BytesN<32>,
-    ) -> bool {
-        let count = proofs.len();
-        
-        if count != data_items.len() || count != salts.len() {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidInput as u32,
-            );
-            return false;
+    /// * `admin` - The address allowed to change contract configuration
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `domain` - Deployment domain folded into every HMAC message (may be empty)
+    pub fn initialize(env: Env, admin: Address, hmac_key: BytesN<32>, domain: Bytes) {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Admin) {
+            panic_with_error!(&env, ContractError::AlreadyInitialized);
         }
 
-        for i in 0..count {
-            let proof = proofs.get(i).unwrap();
-            let data = data_items.get(i).unwrap();
-            let salt = salts.get(i).unwrap();
-
-            if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key.clone()) {
-                env.events().publish(
-                    (Symbol::new(&env, "batch_failed_at"),),
-                    i,
-                );
-                return false;
-            }
-        }
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::HmacKey, &hmac_key);
+        storage.set(&DataKey::Domain, &domain);
+    }
 
-        env.events().publish(
-            (Symbol::new(&env, "batch_verified"),),
-            count,
-        );
+    /// Returns the deployment domain (empty if none was set).
+    pub fn domain(env: Env) -> Bytes {
+        env.storage()
+            .instance()
+            .get(&DataKey::Domain)
+            .unwrap_or_else(|| Bytes::new(&env))
+    }
 
-        true
+    /// Returns the contract admin.
+    pub fn admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::NotInitialized))
     }
 
-    /// Performs structural validation of a Bulletproofs range proof on secp256k1.
+    /// Replaces the registered HMAC key, keeping the previous key valid for
+    /// `grace_ledgers` ledgers so proofs already issued under it still verify
+    /// through `verify_with_stored_key`.
     ///
-    /// **What this checks:**
+    /// Starting a rotation while another is in its grace period drops the older
+    /// previous key immediately.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_key` - The HMAC key to register
+    /// * `grace_ledgers` - How long the previous key stays accepted
+    pub fn begin_rotation(env: Env, new_key: BytesN<32>, grace_ledgers: u32) {
+        Self::require_admin(&env);
+        let storage = env.storage().instance();
+        let previous_key: BytesN<32> = storage
+            .get(&DataKey::HmacKey)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::NotInitialized));
+
+        let rotation = KeyRotation {
+            previous_key,
+            expires_at: env.ledger().sequence().saturating_add(grace_ledgers),
+        };
+        storage.set(&DataKey::KeyRotation, &rotation);
+        storage.set(&DataKey::HmacKey, &new_key);
+    }
+
+    /// Ends a rotation's grace period early, so only the current key is accepted.
+    pub fn finalize_rotation(env: Env) {
+        Self::require_admin(&env);
+        env.storage().instance().remove(&DataKey::KeyRotation);
+    }
+
+    /// Returns the rotation in its grace period, if any.
+    pub fn key_rotation(env: Env) -> Option<KeyRotation> {
+        env.storage()
+            .instance()
+            .get::<_, KeyRotation>(&DataKey::KeyRotation)
+            .filter(|rotation| env.ledger().sequence() < rotation.expires_at)
+    }
+
+    /// Creates a namespace with its own admin, HMAC key and domain; must be
+    /// authorized by the contract admin, so namespace ids can't be claimed by
+    /// anyone else.
+    ///
+    /// Panics with `ContractError::AlreadyInitialized` if the namespace exists.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `namespace` - The namespace id
+    /// * `admin` - The address allowed to configure the namespace (must authorize)
+    /// * `hmac_key` - The namespace's HMAC secret key (32 bytes)
+    /// * `domain` - Domain folded into the namespace's HMAC messages in place of the
+    ///   deployment domain (may be empty)
+    pub fn init_namespace(
+        env: Env,
+        namespace: BytesN<32>,
+        admin: Address,
+        hmac_key: BytesN<32>,
+        domain: Bytes,
+    ) {
+        Self::require_admin(&env);
+        admin.require_auth();
+        let storage = env.storage().persistent();
+        let admin_key = DataKey::Namespaced(namespace.clone(), NamespaceKey::Admin);
+        if storage.has(&admin_key) {
+            panic_with_error!(&env, ContractError::AlreadyInitialized);
+        }
+
+        storage.set(&admin_key, &admin);
+        storage.set(&DataKey::Namespaced(namespace.clone(), NamespaceKey::HmacKey), &hmac_key);
+        storage.set(&DataKey::Namespaced(namespace, NamespaceKey::Domain), &domain);
+    }
+
+    /// Returns a namespace's admin.
+    pub fn namespace_admin(env: Env, namespace: BytesN<32>) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Namespaced(namespace, NamespaceKey::Admin))
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::NotInitialized))
+    }
+
+    /// Replaces a namespace's HMAC key; must be authorized by the namespace admin.
+    pub fn set_namespace_key(env: Env, namespace: BytesN<32>, hmac_key: BytesN<32>) {
+        Self::namespace_admin(env.clone(), namespace.clone()).require_auth();
+        env.storage()
+            .persistent()
+            .set(&DataKey::Namespaced(namespace, NamespaceKey::HmacKey), &hmac_key);
+    }
+
+    /// Sets a namespace's minimum balance for an asset (scaled by the asset's
+    /// decimals), as checked by `verify_balance_in_namespace`; must be authorized
+    /// by the namespace admin.
+    pub fn set_namespace_threshold(
+        env: Env,
+        namespace: BytesN<32>,
+        asset_id: BytesN<32>,
+        required: i128,
+    ) {
+        Self::namespace_admin(env.clone(), namespace.clone()).require_auth();
+        env.storage().persistent().set(
+            &DataKey::Namespaced(namespace, NamespaceKey::RequiredThreshold(asset_id)),
+            &required,
+        );
+    }
+
+    /// Returns a namespace's threshold for an asset, if any.
+    pub fn namespace_threshold(env: Env, namespace: BytesN<32>, asset_id: BytesN<32>) -> Option<i128> {
+        Self::scoped_threshold(&env, Some(&namespace), &asset_id)
+    }
+
+    /// Configures the decimal precision `verify_balance_in_namespace` uses for an
+    /// asset in a namespace; must be authorized by the namespace admin. The
+    /// contract-wide `set_asset_decimals` configuration doesn't apply in
+    /// namespaces.
+    pub fn set_namespace_asset_decimals(
+        env: Env,
+        namespace: BytesN<32>,
+        asset_id: BytesN<32>,
+        decimals: u32,
+    ) {
+        Self::namespace_admin(env.clone(), namespace.clone()).require_auth();
+        env.storage().persistent().set(
+            &DataKey::Namespaced(namespace, NamespaceKey::AssetDecimals(asset_id)),
+            &decimals,
+        );
+    }
+
+    /// Returns the decimal precision configured for an asset in a namespace, if any.
+    pub fn namespace_asset_decimals(env: Env, namespace: BytesN<32>, asset_id: BytesN<32>) -> Option<u32> {
+        Self::scoped_decimals(&env, Some(&namespace), &asset_id)
+    }
+
+    /// Returns how many proofs have verified in a namespace.
+    pub fn namespace_verified_count(env: Env, namespace: BytesN<32>) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Namespaced(namespace, NamespaceKey::VerifiedCount))
+            .unwrap_or(0)
+    }
+
+    /// Proposes a new admin. The transfer only takes effect once `new_admin`
+    /// calls `accept_admin`, so control can't be handed to an address that can't
+    /// sign. A later proposal replaces a pending one.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_admin` - The proposed admin
+    pub fn propose_admin(env: Env, new_admin: Address) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+    }
+
+    /// Completes an admin transfer; must be authorized by the pending admin.
+    ///
+    /// Panics with `ContractError::NoPendingAdmin` if no transfer was proposed.
+    pub fn accept_admin(env: Env) {
+        let storage = env.storage().instance();
+        let pending: Address = storage
+            .get(&DataKey::PendingAdmin)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::NoPendingAdmin));
+        pending.require_auth();
+
+        storage.set(&DataKey::Admin, &pending);
+        storage.remove(&DataKey::PendingAdmin);
+    }
+
+    /// Returns the admin awaiting `accept_admin`, if any.
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Enables or disables debug events and the debug entry points
+    /// (`debug_message`, `verify_proof_debug`).
+    ///
+    /// Debug output is disabled by default. Only enable it on non-production
+    /// deployments where proof inputs are not sensitive.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `enabled` - `true` to publish debug events, `false` to suppress them
+    pub fn set_debug_events(env: Env, enabled: bool) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::DebugEvents, &enabled);
+    }
+
+    /// Returns whether debug events are currently published.
+    pub fn debug_events_enabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::DebugEvents)
+            .unwrap_or(false)
+    }
+
+    /// Returns the exact message `verify_proof` feeds into the HMAC for the given
+    /// inputs, so a prover can diff it against the message they signed.
+    ///
+    /// The layout is `[len(domain) || domain] || data || salt` (see the crate-level
+    /// "Deployment Domain" docs). The secret key is never part of the result.
+    /// Only available while debug events are enabled; panics with
+    /// `ContractError::DebugDisabled` otherwise.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation
+    pub fn debug_message(env: Env, data: Bytes, salt: Bytes) -> Bytes {
+        if !Self::debug_events_enabled(env.clone()) {
+            panic_with_error!(&env, ContractError::DebugDisabled);
+        }
+        Self::build_message(&env, &data, &salt)
+    }
+
+    /// Verifies a proof like `verify_proof` and also returns the HMAC the contract
+    /// computed, so an integrator can diff it against their own.
+    ///
+    /// When input validation fails the validation error is published and
+    /// `(false, zero hash)` is returned without hashing. Only available while
+    /// debug events are enabled; panics with `ContractError::DebugDisabled`
+    /// otherwise.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `(valid, computed_hmac)`
+    pub fn verify_proof_debug(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> (bool, BytesN<32>) {
+        if !Self::debug_events_enabled(env.clone()) {
+            panic_with_error!(&env, ContractError::DebugDisabled);
+        }
+
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return (false, BytesN::from_array(&env, &[0u8; 32]));
+        }
+
+        let message = Self::build_message(&env, &data, &salt);
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+        Self::publish_result(&env, proof.as_ref(), is_valid, "data");
+
+        (is_valid, computed_hmac)
+    }
+
+    /// Returns how many verifications have failed with each error, keyed by the
+    /// `VerificationError` discriminant. A proof that fails its HMAC comparison
+    /// counts as `InvalidProof`; variants that never occurred are absent.
+    pub fn get_error_stats(env: Env) -> Map<u32, u64> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ErrorStats)
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Restricts which hash algorithms the algorithm-parameterized verifiers accept.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `mask` - Bitmask of allowed algorithms (see `HashAlg::mask_bit`)
+    pub fn set_allowed_algs(env: Env, mask: u32) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::AllowedAlgs, &mask);
+    }
+
+    /// Returns the allowed-algorithms mask (`ALL_HASH_ALGS` until the admin sets one).
+    pub fn allowed_algs(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowedAlgs)
+            .unwrap_or(ALL_HASH_ALGS)
+    }
+
+    /// Sets the size limits applied to batch and aggregate inputs.
+    ///
+    /// Together the limits bound the work one call can demand, whether it sends
+    /// many small items or a few large ones.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max_items` - Maximum number of items
+    /// * `max_total_bytes` - Maximum summed length of the data items
+    pub fn set_batch_limits(env: Env, max_items: u32, max_total_bytes: u32) {
+        Self::require_admin(&env);
+        let storage = env.storage().instance();
+        storage.set(&DataKey::MaxItems, &max_items);
+        storage.set(&DataKey::MaxTotalBytes, &max_total_bytes);
+    }
+
+    /// Returns the batch limits as `(max_items, max_total_bytes)`
+    /// (`MAX_ITEMS` and `MAX_TOTAL_BYTES` until the admin sets them).
+    pub fn batch_limits(env: Env) -> (u32, u32) {
+        let storage = env.storage().instance();
+        (
+            storage.get(&DataKey::MaxItems).unwrap_or(MAX_ITEMS),
+            storage.get(&DataKey::MaxTotalBytes).unwrap_or(MAX_TOTAL_BYTES),
+        )
+    }
+
+    /// Requires every item of a batch to use a distinct salt.
+    ///
+    /// While enabled, the batch verifiers reject a batch (with `DuplicateSalt`)
+    /// if any two items share a salt, even when their data differs. Disabled by
+    /// default; `verify_batch_shared_salt` is unaffected since its salt is shared
+    /// by design.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `enabled` - `true` to reject batches that reuse a salt
+    pub fn set_unique_salts(env: Env, enabled: bool) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::UniqueSalts, &enabled);
+    }
+
+    /// Returns whether the batch verifiers require distinct salts.
+    pub fn unique_salts(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::UniqueSalts)
+            .unwrap_or(false)
+    }
+
+    /// Sets the token-bucket capacity used by `verify_proof_token_bucket`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `capacity` - Maximum tokens a bucket holds (the largest burst)
+    pub fn set_bucket_capacity(env: Env, capacity: u32) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::BucketCapacity, &capacity);
+    }
+
+    /// Returns the token-bucket capacity (`DEFAULT_BUCKET_CAPACITY` until set).
+    pub fn bucket_capacity(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::BucketCapacity)
+            .unwrap_or(DEFAULT_BUCKET_CAPACITY)
+    }
+
+    /// Sets how many tokens each bucket regains per ledger.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `refill_per_ledger` - Tokens added per elapsed ledger
+    pub fn set_bucket_refill_per_ledger(env: Env, refill_per_ledger: u32) {
+        Self::require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::BucketRefillPerLedger, &refill_per_ledger);
+    }
+
+    /// Returns the per-ledger refill (`DEFAULT_BUCKET_REFILL_PER_LEDGER` until set).
+    pub fn bucket_refill_per_ledger(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::BucketRefillPerLedger)
+            .unwrap_or(DEFAULT_BUCKET_REFILL_PER_LEDGER)
+    }
+
+    /// Configures the decimal precision used to compare balances of an asset.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `asset_id` - The asset identifier
+    /// * `decimals` - Number of fractional digits (e.g. 7 for USDC on Stellar)
+    pub fn set_asset_decimals(env: Env, asset_id: BytesN<32>, decimals: u32) {
+        Self::require_admin(&env);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AssetDecimals(asset_id), &decimals);
+    }
+
+    /// Returns the decimal precision configured for an asset, if any.
+    pub fn asset_decimals(env: Env, asset_id: BytesN<32>) -> Option<u32> {
+        Self::scoped_decimals(&env, None, &asset_id)
+    }
+
+    /// Sets the minimum balance `verify_balance_against_threshold` requires for an
+    /// asset.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `asset_id` - The asset identifier
+    /// * `required` - The threshold, scaled by the asset's configured decimals
+    pub fn set_required_threshold(env: Env, asset_id: BytesN<32>, required: i128) {
+        Self::require_admin(&env);
+        env.storage()
+            .persistent()
+            .set(&DataKey::RequiredThreshold(asset_id), &required);
+    }
+
+    /// Returns the threshold configured for an asset, if any.
+    pub fn required_threshold(env: Env, asset_id: BytesN<32>) -> Option<i128> {
+        Self::scoped_threshold(&env, None, &asset_id)
+    }
+
+    /// Stores data under `data_key` so later proofs about it can reference the key
+    /// instead of resending the bytes (see `verify_stored_data_proof`).
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `data_key` - The key to store the data under
+    /// * `data` - The data proofs will be checked against
+    pub fn store_data(env: Env, data_key: BytesN<32>, data: Bytes) {
+        Self::require_admin(&env);
+        env.storage()
+            .persistent()
+            .set(&DataKey::StoredData(data_key), &data);
+    }
+
+    /// Stores a commitment `sha256(key)` to the HMAC key accepted by
+    /// `verify_with_committed_key`, so the raw key never has to be stored.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `key_hash` - `sha256` of the 32-byte HMAC key
+    pub fn initialize_key_commitment(env: Env, key_hash: BytesN<32>) {
+        Self::require_admin(&env);
+        env.storage().instance().set(&DataKey::KeyCommitment, &key_hash);
+    }
+
+    /// Adds a member to the on-chain membership set checked by
+    /// `verify_membership_proof`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `member` - The member identifier (e.g. an account hash)
+    pub fn add_member(env: Env, member: BytesN<32>) {
+        Self::require_admin(&env);
+        env.storage().persistent().set(&DataKey::Member(member), &());
+    }
+
+    /// Removes a member from the on-chain membership set.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `member` - The member identifier
+    pub fn remove_member(env: Env, member: BytesN<32>) {
+        Self::require_admin(&env);
+        env.storage().persistent().remove(&DataKey::Member(member));
+    }
+
+    /// Returns whether `member` is in the on-chain membership set.
+    pub fn is_member(env: Env, member: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Member(member))
+    }
+
+    /// Registers a server-issued salt for a single `verify_with_registered_salt`
+    /// call. Re-registering a consumed salt does not make it usable again.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `salt_hash` - `sha256` of the issued salt
+    pub fn register_salt(env: Env, salt_hash: BytesN<32>) {
+        Self::require_admin(&env);
+        let key = DataKey::RegisteredSalt(salt_hash);
+        if !env.storage().persistent().has(&key) {
+            env.storage().persistent().set(&key, &false);
+        }
+    }
+
+    /// Verifies a Zero-Knowledge Proof using HMAC-SHA256.
+    /// 
+    /// This function computes HMAC-SHA256(data + salt) using the provided HMAC key
+    /// and compares it with the provided proof in constant time to prevent timing attacks.
+    /// 
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven (may be empty, at most `MAX_DATA_LEN` bytes)
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// 
+    /// # Returns
+    /// * `true` if the proof is valid
+    /// * `false` if the proof is invalid
+    /// 
+    /// # Examples
+    /// ```
+    /// // Verify a balance proof
+    /// let is_valid = contract.verify_proof(&env, proof, data, salt, hmac_key);
+    /// ```
+    pub fn verify_proof(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::verify_proof_with::<HmacSha256>(env, None, proof, data, salt, hmac_key, "data")
+    }
+
+    /// Verifies a proof like `verify_proof`, returning a structured result that
+    /// carries the failure reason and the ledger it was checked at.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * A `VerificationResult` whose `reason` is `VerificationError::None` on success
+    pub fn verify_proof_meta(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> VerificationResult {
+        let reason = Self::proof_reason(&env, &proof, &data, &salt, &hmac_key);
+
+        VerificationResult {
+            valid: reason == VerificationError::None,
+            reason,
+            ledger: env.ledger().sequence(),
+        }
+    }
+
+    /// Decodes a standard (RFC 4648) base64-encoded 32-byte proof.
+    ///
+    /// The input must be exactly 44 characters: 43 alphabet characters followed by
+    /// a single `=` pad. The unused low bits of the last character must be zero, so
+    /// every proof has exactly one accepted encoding.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `b64` - The base64-encoded proof
+    ///
+    /// # Returns
+    /// * The decoded proof, or `None` on a wrong length or invalid character
+    pub fn decode_base64_proof(env: Env, b64: Bytes) -> Option<BytesN<32>> {
+        if b64.len() != 44 || b64.get(43)? != b'=' {
+            return None;
+        }
+
+        let mut out = [0u8; 32];
+        let mut acc: u32 = 0;
+        let mut bits: u32 = 0;
+        let mut pos = 0usize;
+        for i in 0..43 {
+            acc = (acc << 6) | Self::base64_value(b64.get(i)?)? as u32;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                out[pos] = (acc >> bits) as u8;
+                pos += 1;
+            }
+        }
+
+        // 43 * 6 = 258 bits: the 2 leftover bits must be zero
+        if acc & ((1 << bits) - 1) != 0 {
+            return None;
+        }
+
+        Some(BytesN::from_array(&env, &out))
+    }
+
+    /// Verifies a base64-encoded proof like `verify_proof`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof_b64` - The proof, base64-encoded (see `decode_base64_proof`)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof decodes and is valid
+    pub fn verify_proof_base64(
+        env: Env,
+        proof_b64: Bytes,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        match Self::decode_base64_proof(env.clone(), proof_b64) {
+            Some(proof) => Self::verify_proof(env, proof, data, salt, hmac_key),
+            None => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        }
+    }
+
+    /// Verifies a proof transmitted together with its salt as one `salt || proof`
+    /// blob, as by `verify_proof`.
+    ///
+    /// The last 32 bytes are the MAC; everything before them is the salt, which
+    /// must still be at least the minimum salt length.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `packed` - `salt || proof`
+    /// * `data` - The original data that was proven
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the blob is well-formed and the proof is valid
+    pub fn verify_packed(env: Env, packed: Bytes, data: Bytes, hmac_key: BytesN<32>) -> bool {
+        if packed.len() < MIN_SALT_LEN + 32 {
+            Self::publish_error(&env, VerificationError::InvalidProofLength);
+            return false;
+        }
+
+        let split = packed.len() - 32;
+        let salt = packed.slice(..split);
+        let proof: BytesN<32> = packed.slice(split..).try_into().unwrap();
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a proof that may have been made under any one of several keys,
+    /// e.g. the current and previous key during a rotation grace period.
+    ///
+    /// Every key is checked, without stopping at the first match, so the time
+    /// taken does not reveal which key (if any) matched. Cost grows linearly with
+    /// the number of keys.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `keys` - The candidate HMAC keys
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid under at least one key
+    pub fn verify_any_key(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        keys: Vec<BytesN<32>>,
+    ) -> bool {
+        if keys.is_empty() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return false;
+        }
+
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &data, &salt);
+        let mut is_valid = false;
+        for key in keys.iter() {
+            let computed_hmac = Self::compute_hmac(&env, &message, &key);
+            // Non-short-circuiting `|` so every key is always checked
+            is_valid = core::hint::black_box(is_valid | Self::secure_compare(&proof, &computed_hmac));
+        }
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "data");
+
+        is_valid
+    }
+
+    /// Verifies a proof under the registered HMAC key, or under the previous key
+    /// while a `begin_rotation` grace period is open. Both keys are checked as in
+    /// `verify_any_key`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid under a currently accepted key
+    pub fn verify_with_stored_key(env: Env, proof: BytesN<32>, data: Bytes, salt: Bytes) -> bool {
+        let current = Self::stored_key(&env, None);
+
+        let mut keys = Vec::from_array(&env, [current]);
+        if let Some(rotation) = Self::key_rotation(env.clone()) {
+            keys.push_back(rotation.previous_key);
+        }
+
+        Self::verify_any_key(env, proof, data, salt, keys)
+    }
+
+    /// Verifies a proof under a key fetched from a key provider contract, so the
+    /// key is never stored by (or passed to) this contract.
+    ///
+    /// The provider must expose `get_key(key_id: BytesN<32>) -> Option<BytesN<32>>`.
+    /// A missing key or a failing call (no such contract or function, a panic, or
+    /// a result of another type) is reported as `KeyUnavailable` instead of
+    /// aborting the invocation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `provider` - The key provider contract
+    /// * `key_id` - The id of the HMAC key to fetch from the provider
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    ///
+    /// # Returns
+    /// * `true` if the provider returned a key and the proof is valid under it
+    pub fn verify_with_provider(
+        env: Env,
+        provider: Address,
+        key_id: BytesN<32>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+    ) -> bool {
+        let key = env.try_invoke_contract::<Option<BytesN<32>>, InvokeError>(
+            &provider,
+            &Symbol::new(&env, "get_key"),
+            Vec::from_array(&env, [key_id.into_val(&env)]),
+        );
+        let hmac_key = match key {
+            Ok(Ok(Some(hmac_key))) => hmac_key,
+            _ => {
+                Self::publish_error(&env, VerificationError::KeyUnavailable);
+                return false;
+            }
+        };
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a proof like `verify_proof` under a namespace's HMAC key and
+    /// domain, counting successes in `namespace_verified_count`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `namespace` - The namespace whose key the proof was made with
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid under the namespace's key
+    pub fn verify_in_namespace(
+        env: Env,
+        namespace: BytesN<32>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+    ) -> bool {
+        let scope = Some(&namespace);
+        let hmac_key = Self::stored_key(&env, scope);
+        let is_valid =
+            Self::verify_proof_with::<HmacSha256>(env.clone(), scope, proof, data, salt, hmac_key, "data");
+        if is_valid {
+            Self::count_verification(&env, scope);
+        }
+        is_valid
+    }
+
+    /// Verifies an asset balance proof like `verify_balance_against_threshold`,
+    /// under a namespace's HMAC key and domain and against the namespace's
+    /// decimals and threshold for the asset (see `set_namespace_asset_decimals`
+    /// and `set_namespace_threshold`), counting successes in
+    /// `namespace_verified_count`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `namespace` - The namespace whose key and configuration apply
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `asset_id` - The asset identifier (decimals and threshold must be configured
+    ///   in the namespace)
+    /// * `salt` - The cryptographic salt
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and balance >= the namespace's threshold
+    pub fn verify_balance_in_namespace(
+        env: Env,
+        namespace: BytesN<32>,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        asset_id: BytesN<32>,
+        salt: Bytes,
+    ) -> bool {
+        let scope = Some(&namespace);
+        let hmac_key = Self::stored_key(&env, scope);
+        Self::check_balance_threshold(&env, scope, &proof, &balance_data, &asset_id, &salt, &hmac_key)
+    }
+
+    /// Verifies a proof like `verify_proof_once` under a namespace's HMAC key and
+    /// domain, with a replay store separate from every other namespace, counting
+    /// successes in `namespace_verified_count`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `namespace` - The namespace whose key the proof was made with
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and was not consumed in this namespace
+    ///   within its replay window
+    pub fn verify_once_in_namespace(
+        env: Env,
+        namespace: BytesN<32>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+    ) -> bool {
+        let hmac_key = Self::stored_key(&env, Some(&namespace));
+        Self::verify_and_consume(&env, Some(&namespace), proof, data, salt, hmac_key)
+    }
+
+    /// Verifies an HMAC proof computed with the given hash algorithm.
+    ///
+    /// The message layout is the same as `verify_proof` (`data || salt`); only the
+    /// underlying hash differs. Algorithms outside the admin-configured allowed set
+    /// are rejected before hashing.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The MAC to verify (32 bytes for SHA-256, 64 bytes for SHA-512)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `alg` - The hash algorithm the proof was computed with
+    ///
+    /// # Returns
+    /// * `true` if the algorithm is allowed and the proof is valid
+    pub fn verify_proof_alg(
+        env: Env,
+        proof: Bytes,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        alg: HashAlg,
+    ) -> bool {
+        if Self::allowed_algs(env.clone()) & alg.mask_bit() == 0 {
+            Self::publish_error(&env, VerificationError::DisallowedAlgorithm);
+            return false;
+        }
+
+        if !Self::validate_mac_len(&env, &proof, alg.mac_len()) {
+            return false;
+        }
+
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &data, &salt);
+        let computed_hmac = Self::compute_hmac_alg(&env, &message, &hmac_key, alg);
+        let is_valid = Self::secure_compare_bytes(&proof, &computed_hmac);
+
+        Self::publish_result(&env, &proof, is_valid, "data");
+
+        is_valid
+    }
+
+    /// Verifies a self-describing proof: a 4-byte header followed by the MAC.
+    ///
+    /// Header layout: `version (1 byte) || HashAlg (1 byte) || reserved (2 zero bytes)`.
+    /// Only `TAGGED_PROOF_VERSION` is accepted. The MAC is then checked as by
+    /// `verify_proof_alg` with the algorithm named in the header, so the allowed
+    /// algorithms mask still applies.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `tagged_proof` - Header followed by the MAC
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the header is recognized and the MAC is valid
+    pub fn verify_proof_tagged(
+        env: Env,
+        tagged_proof: Bytes,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if tagged_proof.len() < TAGGED_PROOF_HEADER_LEN {
+            Self::publish_error(&env, VerificationError::InvalidProofLength);
+            return false;
+        }
+
+        let version = tagged_proof.get(0).unwrap();
+        let alg = HashAlg::from_u32(tagged_proof.get(1).unwrap() as u32);
+        let reserved = [tagged_proof.get(2).unwrap(), tagged_proof.get(3).unwrap()];
+        let alg = match alg {
+            Some(alg) if version == TAGGED_PROOF_VERSION && reserved == [0, 0] => alg,
+            _ => {
+                Self::publish_error(&env, VerificationError::UnknownProofHeader);
+                return false;
+            }
+        };
+
+        let mac = tagged_proof.slice(TAGGED_PROOF_HEADER_LEN..);
+        Self::verify_proof_alg(env, mac, data, salt, hmac_key, alg)
+    }
+
+    /// Verifies a proof on behalf of a submitter, rate-limited per submitter.
+    ///
+    /// Each submitter may perform at most `RATE_LIMIT_MAX_CALLS` verifications per
+    /// `RATE_LIMIT_WINDOW` ledgers; the window restarts on the first call after it
+    /// has elapsed. Every call counts against the limit, whether or not the proof
+    /// is valid.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `submitter` - The address submitting the proof (must authorize the call)
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the submitter is within its limit and the proof is valid
+    pub fn verify_proof_rate_limited(
+        env: Env,
+        submitter: Address,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::verify_rate_limited_allowance(env, submitter, proof, data, salt, hmac_key).0
+    }
+
+    /// Verifies a proof like `verify_proof_rate_limited`, also returning the
+    /// submitter's remaining allowance after this call.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `submitter` - The address submitting the proof (must authorize the call)
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `(is_valid, remaining)`, where `remaining` is what `remaining_allowance`
+    ///   would now report
+    pub fn verify_rate_limited_allowance(
+        env: Env,
+        submitter: Address,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> (bool, u32) {
+        submitter.require_auth();
+
+        let mut window = match Self::current_rate_window(&env, &submitter) {
+            Some(window) => window,
+            None => {
+                Self::publish_error(&env, VerificationError::LedgerInFuture);
+                return (false, 0);
+            }
+        };
+
+        if window.count >= RATE_LIMIT_MAX_CALLS {
+            Self::publish_error(&env, VerificationError::RateLimited);
+            return (false, 0);
+        }
+
+        window.count += 1;
+        env.storage().persistent().set(&DataKey::RateLimit(submitter), &window);
+
+        let is_valid = Self::verify_proof(env, proof, data, salt, hmac_key);
+        (is_valid, RATE_LIMIT_MAX_CALLS - window.count)
+    }
+
+    /// Returns how many more calls `submitter` may make to
+    /// `verify_proof_rate_limited` in the current window, without consuming one.
+    ///
+    /// A window that has elapsed counts as already reset. Stored state ahead of
+    /// the current ledger reports no allowance, matching the verify path.
+    pub fn remaining_allowance(env: Env, submitter: Address) -> u32 {
+        Self::current_rate_window(&env, &submitter)
+            .map_or(0, |window| RATE_LIMIT_MAX_CALLS.saturating_sub(window.count))
+    }
+
+    /// Verifies a proof on behalf of a submitter, rate-limited by a per-submitter
+    /// token bucket.
+    ///
+    /// A bucket starts full at `bucket_capacity` tokens and regains
+    /// `bucket_refill_per_ledger` tokens per ledger, never beyond the capacity.
+    /// Each call spends one token, whether or not the proof is valid; a call with
+    /// no token left is rejected. Unlike `verify_proof_rate_limited`, throughput
+    /// recovers gradually instead of resetting at a window boundary.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `submitter` - The address submitting the proof (must authorize the call)
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the submitter had a token and the proof is valid
+    pub fn verify_proof_token_bucket(
+        env: Env,
+        submitter: Address,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        submitter.require_auth();
+
+        let key = DataKey::TokenBucket(submitter);
+        let now = env.ledger().sequence();
+        let capacity = Self::bucket_capacity(env.clone());
+        let bucket: TokenBucket = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(TokenBucket { tokens: capacity, last_refill: now });
+
+        let elapsed = match ledger_gap(now, bucket.last_refill) {
+            Some(elapsed) => elapsed as u64,
+            None => {
+                Self::publish_error(&env, VerificationError::LedgerInFuture);
+                return false;
+            }
+        };
+        // u32 * u32 fits in u64, so any ledger gap refills without overflow
+        let refill = elapsed * Self::bucket_refill_per_ledger(env.clone()) as u64;
+        let tokens = (bucket.tokens as u64 + refill).min(capacity as u64) as u32;
+
+        if tokens == 0 {
+            Self::publish_error(&env, VerificationError::RateLimited);
+            return false;
+        }
+
+        env.storage().persistent().set(
+            &key,
+            &TokenBucket { tokens: tokens - 1, last_refill: now },
+        );
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a single aggregate proof covering an ordered list of data items.
+    ///
+    /// The HMAC message is `len(item_0) || item_0 || ... || len(item_n) || item_n || salt`
+    /// with each length a u32 big-endian prefix. The prefixes make the encoding
+    /// unambiguous: `["ab", "c"]` and `["a", "bc"]` hash differently even though
+    /// their plain concatenations are equal.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `aggregate_proof` - The proof over all items (32 bytes HMAC-SHA256 output)
+    /// * `data_items` - The proven data items, in the order the prover used
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the aggregate proof is valid
+    pub fn verify_aggregate(
+        env: Env,
+        aggregate_proof: BytesN<32>,
+        data_items: Vec<Bytes>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &data_items) {
+            return false;
+        }
+
+        let mut encoded = Bytes::new(&env);
+        for item in data_items.iter() {
+            Self::append_length_prefixed(&mut encoded, &item);
+        }
+
+        if !Self::validate_inputs(&env, &encoded, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &encoded, &salt);
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&aggregate_proof, &computed_hmac);
+
+        Self::publish_result(&env, aggregate_proof.as_ref(), is_valid, "aggregate");
+
+        is_valid
+    }
+
+    /// Verifies a proof over a structured multi-field `Statement`.
+    ///
+    /// The statement is serialized in fixed field order, each field prefixed with
+    /// its u32 big-endian length: `account || asset || balance || timestamp`, where
+    /// `account` is the address XDR and `timestamp` is 8 bytes big-endian. The HMAC
+    /// message is the serialized statement followed by the salt.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `statement` - The proven statement
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid for exactly this statement
+    pub fn verify_statement(
+        env: Env,
+        proof: BytesN<32>,
+        statement: Statement,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let encoded = Self::serialize_statement(&env, &statement);
+
+        if !Self::validate_inputs(&env, &encoded, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &encoded, &salt);
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "statement");
+
+        is_valid
+    }
+
+    /// Verifies a proof over a structured record (e.g. account, asset, amount,
+    /// memo) after checking it against a `Schema`.
+    ///
+    /// The record must have exactly one segment per `schema.max_lengths` entry,
+    /// each non-empty and no longer than its maximum; otherwise it is rejected
+    /// with `SchemaMismatch` before hashing. The HMAC message uses the
+    /// `verify_aggregate` layout: `len(segment_0) || segment_0 || ... || salt`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `segments` - The record's segments, in schema order
+    /// * `schema` - The expected shape of the record
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the record matches the schema and the proof is valid
+    pub fn verify_schema_proof(
+        env: Env,
+        proof: BytesN<32>,
+        segments: Vec<Bytes>,
+        schema: Schema,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let conforms = segments.len() == schema.max_lengths.len()
+            && segments
+                .iter()
+                .zip(schema.max_lengths.iter())
+                .all(|(segment, max_len)| !segment.is_empty() && segment.len() <= max_len);
+        if !conforms {
+            Self::publish_error(&env, VerificationError::SchemaMismatch);
+            return false;
+        }
+
+        let mut encoded = Bytes::new(&env);
+        for segment in segments.iter() {
+            Self::append_length_prefixed(&mut encoded, &segment);
+        }
+
+        if !Self::validate_inputs(&env, &encoded, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &encoded, &salt);
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "schema");
+
+        is_valid
+    }
+
+    /// Verifies a proof over the XDR encoding of a Soroban value.
+    ///
+    /// `value` is serialized with `ToXdr` (its `ScVal` XDR), which is canonical:
+    /// struct fields and map keys are encoded in sorted order, so a prover that
+    /// calls `to_xdr` on the same value produces the same bytes. The HMAC message
+    /// is the serialized value followed by the salt.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `value` - The proven value (e.g. a `#[contracttype]` struct)
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid for exactly this value
+    pub fn verify_val_proof(
+        env: Env,
+        proof: BytesN<32>,
+        value: Val,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let encoded = value.to_xdr(&env);
+        Self::verify_proof(env, proof, encoded, salt, hmac_key)
+    }
+
+    /// Verifies a proof and, only if it is valid, invokes `fn_name` on `target`.
+    ///
+    /// An in-progress flag is held in storage around the cross-contract call; any
+    /// re-entrant call while it is set is rejected without verifying or invoking.
+    /// A panic in the target aborts the whole invocation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `target` - The contract to invoke on success
+    /// * `fn_name` - The function to invoke on `target`
+    /// * `args` - The arguments passed to `fn_name`
+    ///
+    /// # Returns
+    /// * `true` if the proof was valid and `target` was invoked
+    pub fn verify_then_invoke(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        target: Address,
+        fn_name: Symbol,
+        args: Vec<Val>,
+    ) -> bool {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::InvokeInProgress) {
+            Self::publish_error(&env, VerificationError::ReentrantCall);
+            return false;
+        }
+
+        if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key) {
+            return false;
+        }
+
+        storage.set(&DataKey::InvokeInProgress, &true);
+        env.invoke_contract::<Val>(&target, &fn_name, args);
+        storage.remove(&DataKey::InvokeInProgress);
+
+        env.events().publish(
+            (Symbol::new(&env, "invoked"),),
+            (target, fn_name),
+        );
+
+        true
+    }
+
+    /// Verifies a proof that `account` must hold at least `required` of `token`,
+    /// and checks the account's live balance with the token's `balance()`.
+    ///
+    /// The HMAC message is `account || required || salt`, where `account` is the
+    /// address XDR and `required` is the 16-byte big-endian `i128` in the token's
+    /// base units. A failing `balance()` call (not a token contract, a panic, or
+    /// a non-`i128` result) is reported as `BalanceFetchFailed` instead of
+    /// aborting the invocation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `token` - The token contract (e.g. a Stellar Asset Contract)
+    /// * `account` - The account whose balance is checked
+    /// * `required` - The minimum balance, in the token's base units
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and the live balance is at least `required`
+    pub fn verify_live_balance_proof(
+        env: Env,
+        proof: BytesN<32>,
+        token: Address,
+        account: Address,
+        required: i128,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let mut data = account.clone().to_xdr(&env);
+        data.extend_from_array(&required.to_be_bytes());
+
+        if !Self::verify_proof_with::<HmacSha256>(
+            env.clone(),
+            None,
+            proof,
+            data,
+            salt,
+            hmac_key,
+            "live_balance",
+        ) {
+            return false;
+        }
+
+        let balance = env.try_invoke_contract::<i128, InvokeError>(
+            &token,
+            &Symbol::new(&env, "balance"),
+            Vec::from_array(&env, [account.into_val(&env)]),
+        );
+        let balance = match balance {
+            Ok(Ok(balance)) => balance,
+            _ => {
+                Self::publish_error(&env, VerificationError::BalanceFetchFailed);
+                return false;
+            }
+        };
+
+        let sufficient = balance >= required;
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            sufficient,
+        );
+
+        sufficient
+    }
+
+    /// Verifies a proof computed with the given MAC construction.
+    ///
+    /// `MacMode::PrefixMac` (`SHA-256(key || data || salt)`) is length-extension
+    /// prone: anyone holding a valid proof can forge proofs for extended messages
+    /// without the key. It exists only for interop with a partner protocol; use
+    /// `MacMode::Hmac` for everything else.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The MAC secret key (32 bytes)
+    /// * `mode` - The MAC construction the proof was computed with
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid
+    pub fn verify_proof_mode(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        mode: MacMode,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &data, &salt);
+        let computed_mac = match mode {
+            MacMode::Hmac => Self::compute_hmac(&env, &message, &hmac_key),
+            MacMode::PrefixMac => {
+                let mut prefixed: Bytes = hmac_key.into();
+                prefixed.append(&message);
+                env.crypto().sha256(&prefixed).into()
+            }
+        };
+        let is_valid = Self::secure_compare(&proof, &computed_mac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "data");
+
+        is_valid
+    }
+
+    /// Verifies a proof like `verify_proof`, caching a positive result for
+    /// `VERIFY_CACHE_TTL` ledgers so repeated checks of the same proof skip the HMAC.
+    ///
+    /// The cache entry is keyed by `sha256(proof || data_hash)`, where
+    /// `data_hash = sha256(hmac_key || message)` and `message` is the full HMAC
+    /// message (domain prefix, data and salt), so a cached result only ever
+    /// answers for the exact inputs it was computed from. Only successes are
+    /// cached; a failed proof is re-checked every time. This is not replay
+    /// protection: a cached proof can be checked any number of times.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid (or was found valid within the cache TTL)
+    pub fn verify_proof_cached(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let message = Self::build_message(&env, &data, &salt);
+        let mut keyed_message = Bytes::from_array(&env, &hmac_key.to_array());
+        keyed_message.append(&message);
+        let data_hash: BytesN<32> = env.crypto().sha256(&keyed_message).into();
+        let mut cache_id = Bytes::from_array(&env, &proof.to_array());
+        cache_id.append(&data_hash.into());
+        let key = DataKey::VerifyCache(env.crypto().sha256(&cache_id).into());
+
+        let storage = env.storage().temporary();
+        if storage.has(&key) {
+            env.events().publish((Symbol::new(&env, "verify_cache_hit"),), true);
+            Self::publish_result(&env, proof.as_ref(), true, "data");
+            return true;
+        }
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "data");
+
+        if is_valid {
+            storage.set(&key, &());
+            storage.extend_ttl(&key, VERIFY_CACHE_TTL, VERIFY_CACHE_TTL);
+        }
+
+        is_valid
+    }
+
+    /// Verifies a proof and consumes it, rejecting any reuse of the same proof
+    /// within `REPLAY_WINDOW` ledgers.
+    ///
+    /// Consumed proofs are recorded under their proof id (`sha256(proof)`) together
+    /// with the ledger from which the record may be evicted. Once that ledger is
+    /// reached the replay window has closed: the proof is accepted again, and
+    /// `sweep_expired` can delete the record to reclaim storage.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and was not consumed within its replay window
+    pub fn verify_proof_once(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::verify_and_consume(&env, None, proof, data, salt, hmac_key)
+    }
+
+    /// Deletes replay records whose replay window has closed.
+    ///
+    /// Records that are missing or still inside their window are left untouched,
+    /// so anyone may call this maintenance method.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof_hashes` - Proof ids (`sha256(proof)`) of the records to sweep
+    ///
+    /// # Returns
+    /// * The number of records removed
+    pub fn sweep_expired(env: Env, proof_hashes: Vec<BytesN<32>>) -> u32 {
+        let storage = env.storage().persistent();
+        let now = env.ledger().sequence();
+        let mut removed: u32 = 0;
+
+        for proof_hash in proof_hashes.iter() {
+            let key = DataKey::ConsumedProof(proof_hash);
+            let evict_at: Option<u32> = storage.get(&key);
+            if evict_at.is_some_and(|evict_at| now >= evict_at) {
+                storage.remove(&key);
+                removed += 1;
+            }
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "replay_swept"),),
+            removed,
+        );
+
+        removed
+    }
+
+    /// Verifies a proof for a single-use claim (airdrops, rewards) and marks the
+    /// claim as used, rejecting every later call for the same `claim_id`.
+    ///
+    /// The HMAC message is `data || salt || claim_id`, so a proof made for one
+    /// claim can't be replayed under another id. Unlike `verify_proof_once`, a
+    /// claim never expires.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `claim_id` - The claim identifier bound into the proof
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the claim was unused and the proof is valid
+    pub fn claim_once(
+        env: Env,
+        claim_id: BytesN<32>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let key = DataKey::Claimed(claim_id.clone());
+        if env.storage().persistent().has(&key) {
+            Self::publish_error(&env, VerificationError::AlreadyClaimed);
+            return false;
+        }
+
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        message.append(&claim_id.clone().into());
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "claim");
+
+        if is_valid {
+            env.storage().persistent().set(&key, &());
+        }
+
+        is_valid
+    }
+
+    /// Returns whether `claim_id` has been claimed through `claim_once`.
+    pub fn is_claimed(env: Env, claim_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Claimed(claim_id))
+    }
+
+    /// Verifies a proof like `verify_proof`, accepting only a salt registered with
+    /// `register_salt` that no earlier successful verification has used. The salt
+    /// is consumed on success; a failed verification leaves it usable.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The registered salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the salt was registered and unused and the proof is valid
+    pub fn verify_with_registered_salt(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let key = DataKey::RegisteredSalt(env.crypto().sha256(&salt).into());
+        match env.storage().persistent().get::<_, bool>(&key) {
+            None => {
+                Self::publish_error(&env, VerificationError::SaltNotRegistered);
+                return false;
+            }
+            Some(true) => {
+                Self::publish_error(&env, VerificationError::SaltConsumed);
+                return false;
+            }
+            Some(false) => {}
+        }
+
+        if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key) {
+            return false;
+        }
+
+        env.storage().persistent().set(&key, &true);
+        true
+    }
+
+    /// Verifies the next proof of a consecutively numbered stream.
+    ///
+    /// The HMAC message is `data || salt || stream_id || seq` (`seq` as 8 bytes
+    /// big-endian). Each stream starts at 0 and only accepts `seq == next_seq`,
+    /// advancing on success, so a skipped, reordered or replayed number is
+    /// rejected with `SequenceMismatch`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `stream_id` - The stream the proof belongs to
+    /// * `seq` - The proof's sequence number within the stream
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if `seq` is the stream's next number and the proof is valid
+    pub fn verify_sequenced(
+        env: Env,
+        stream_id: BytesN<32>,
+        seq: u64,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let next_seq = Self::next_seq(env.clone(), stream_id.clone());
+        if seq != next_seq {
+            Self::publish_error(&env, VerificationError::SequenceMismatch);
+            return false;
+        }
+
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        message.append(&stream_id.clone().into());
+        message.extend_from_array(&seq.to_be_bytes());
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "sequenced");
+
+        if is_valid {
+            env.storage()
+                .persistent()
+                .set(&DataKey::NextSeq(stream_id), &(seq + 1));
+        }
+
+        is_valid
+    }
+
+    /// Returns the next sequence number `verify_sequenced` accepts for a stream.
+    pub fn next_seq(env: Env, stream_id: BytesN<32>) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::NextSeq(stream_id))
+            .unwrap_or(0)
+    }
+
+    /// Returns the tip of the audit log hash chain (all zeros while the log is empty).
+    ///
+    /// Every proof comparison stores an `AuditEntry` (see `log_entry`) and
+    /// advances the head through `audit_chain_hash`, so altering any past entry
+    /// changes every later hash; replaying the stored entries from index 0 must
+    /// reproduce this head.
+    pub fn log_head(env: Env) -> BytesN<32> {
+        Self::audit_log(&env).1
+    }
+
+    /// Returns the number of entries in the audit log.
+    pub fn log_length(env: Env) -> u32 {
+        Self::audit_log(&env).0
+    }
+
+    /// Returns the audit log entry at `index`, if there is one.
+    pub fn log_entry(env: Env, index: u32) -> Option<AuditEntry> {
+        env.storage().persistent().get(&DataKey::AuditEntry(index))
+    }
+
+    /// Runs the contract's HMAC-SHA256 over known-answer vectors baked into the
+    /// contract, so operators can confirm on-chain that the host crypto and the
+    /// HMAC construction behave as expected: RFC 4231 test case 2 through the raw
+    /// HMAC, and a domain-separated `data || salt` proof under a 32-byte key
+    /// through the same message construction `verify_proof` uses.
+    ///
+    /// # Returns
+    /// * `true` only if every computed HMAC matches its expected output exactly
+    pub fn self_test(env: Env) -> bool {
+        let key = Bytes::from_slice(&env, SELF_TEST_KEY);
+        let message = Bytes::from_slice(&env, SELF_TEST_MESSAGE);
+        let expected = BytesN::from_array(&env, &SELF_TEST_HMAC);
+        let computed = Self::compute_hmac_raw_key(&env, &message, &key);
+        let raw_passed = Self::secure_compare(&computed, &expected);
+
+        let vector = &test_vectors::BALANCE_SALT_16;
+        let domain = Bytes::from_slice(&env, SELF_TEST_DOMAIN);
+        let data = Bytes::from_slice(&env, vector.data);
+        let salt = Bytes::from_slice(&env, vector.salt);
+        let message = Self::build_message_for_domain(&env, &domain, &data, &salt);
+        let computed = Self::compute_hmac(&env, &message, &BytesN::from_array(&env, &vector.key));
+        let expected = BytesN::from_array(&env, &SELF_TEST_PROOF);
+        let proof_passed = Self::secure_compare(&computed, &expected);
+
+        let passed = raw_passed && proof_passed;
+
+        env.events().publish((Symbol::new(&env, "self_test"),), passed);
+
+        passed
+    }
+
+    /// Returns the id under which a proof is tracked: `sha256(proof)`.
+    pub fn proof_id(env: Env, proof: BytesN<32>) -> BytesN<32> {
+        env.crypto().sha256(&proof.into()).into()
+    }
+
+    /// Derives a 32-byte HMAC key from a password with PBKDF2-HMAC-SHA256.
+    ///
+    /// The derived key can be passed as `hmac_key` to any verifier. Derivation is
+    /// expensive on-chain: each iteration costs one HMAC-SHA256 (two host SHA-256
+    /// calls), so `iterations` is capped at `MAX_PBKDF2_ITERATIONS`. Prefer deriving
+    /// off-chain and only using this to check a derivation matches.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `password` - The password (any length)
+    /// * `salt` - The PBKDF2 salt (any length)
+    /// * `iterations` - The iteration count, `1..=MAX_PBKDF2_ITERATIONS`
+    ///
+    /// # Returns
+    /// * The first (and only) 32-byte PBKDF2 output block
+    pub fn derive_key_pbkdf2(env: Env, password: Bytes, salt: Bytes, iterations: u32) -> BytesN<32> {
+        if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+            panic_with_error!(&env, ContractError::InvalidIterations);
+        }
+
+        // U_1 = HMAC(P, S || INT(1))
+        let mut message = salt;
+        message.extend_from_array(&1u32.to_be_bytes());
+        let mut block = Self::compute_hmac_raw_key(&env, &message, &password);
+        let mut derived = block.to_array();
+
+        // U_i = HMAC(P, U_{i-1}); T_1 = U_1 ^ ... ^ U_c
+        for _ in 1..iterations {
+            block = Self::compute_hmac_raw_key(&env, &block.into(), &password);
+            for (out, byte) in derived.iter_mut().zip(block.to_array()) {
+                *out ^= byte;
+            }
+        }
+
+        BytesN::from_array(&env, &derived)
+    }
+
+    /// Verifies a nested (NMAC-style) proof: `HMAC(K, HMAC(K, tag || data || salt))`.
+    ///
+    /// Use this when proofs are embedded inside larger messages. The inner message is
+    /// prefixed with a dedicated domain tag, so a single-HMAC proof never validates
+    /// here and a nested proof never validates under `verify_proof`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The nested proof to verify (32 bytes)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid
+    pub fn verify_proof_nested(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        // Inner message: [deployment domain] || domain tag || data || salt
+        let mut message = Self::domain_prefix(&env);
+        message.extend_from_slice(NESTED_HMAC_DOMAIN);
+        message.append(&data);
+        message.append(&salt);
+
+        let inner = Self::compute_hmac(&env, &message, &hmac_key);
+        let computed_hmac = Self::compute_hmac(&env, &inner.into(), &hmac_key);
+
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "nested");
+
+        is_valid
+    }
+
+    /// Verifies a proof like `verify_proof`, also returning how many SHA-256
+    /// invocations its HMAC performed, as a portable cost proxy.
+    ///
+    /// The count covers HMAC-internal hashes only: `SHA256_PER_HMAC` for a
+    /// checked proof, and `0` when the inputs are rejected before hashing. It does
+    /// not include the SHA-256 calls every checked proof also makes outside the
+    /// HMAC (hashing the proof id and advancing the audit log), which are the same
+    /// for every verifier.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `(is_valid, hmac_sha256_count)`
+    pub fn verify_proof_instrumented(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> (bool, u32) {
+        let hashed = Self::check_inputs(&data, &salt).is_ok();
+        let hashes = if hashed { SHA256_PER_HMAC } else { 0 };
+        (Self::verify_proof(env, proof, data, salt, hmac_key), hashes)
+    }
+
+    /// Verifies a nested proof like `verify_proof_nested`, also returning the
+    /// HMAC-internal SHA-256 count described on `verify_proof_instrumented`: two
+    /// HMACs, so `2 * SHA256_PER_HMAC`, or `0` when the inputs are rejected before
+    /// hashing.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The nested proof to verify (32 bytes)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `(is_valid, hmac_sha256_count)`
+    pub fn verify_nested_instrumented(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> (bool, u32) {
+        let hashed = Self::check_inputs(&data, &salt).is_ok();
+        let hashes = if hashed { 2 * SHA256_PER_HMAC } else { 0 };
+        (Self::verify_proof_nested(env, proof, data, salt, hmac_key), hashes)
+    }
+
+    /// Verifies a proof like `verify_proof`, after checking that `hmac_key` matches
+    /// the stored key commitment (see `initialize_key_commitment`).
+    ///
+    /// This only guarantees the caller used the committed key; the raw key is
+    /// still passed in the call.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key, which must hash to the commitment
+    ///
+    /// # Returns
+    /// * `true` if the key matches the commitment and the proof is valid
+    pub fn verify_with_committed_key(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let commitment: BytesN<32> = env
+            .storage()
+            .instance()
+            .get(&DataKey::KeyCommitment)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::NotInitialized));
+
+        let key_hash: BytesN<32> = env.crypto().sha256(&hmac_key.clone().into()).into();
+        if !Self::secure_compare(&key_hash, &commitment) {
+            Self::publish_error(&env, VerificationError::KeyCommitmentMismatch);
+            return false;
+        }
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a proof like `verify_proof`, over data loaded from storage (see
+    /// `store_data`) rather than passed inline.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data_key` - The key the proven data is stored under
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if data is stored under `data_key` and the proof is valid over it
+    pub fn verify_stored_data_proof(
+        env: Env,
+        proof: BytesN<32>,
+        data_key: BytesN<32>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let data: Bytes = match env
+            .storage()
+            .persistent()
+            .get(&DataKey::StoredData(data_key))
+        {
+            Some(data) => data,
+            None => {
+                Self::publish_error(&env, VerificationError::MissingData);
+                return false;
+            }
+        };
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a proof over the SHA-256 hash of some data, without seeing the data.
+    ///
+    /// The prover computes `sha256(data)` off-chain and builds the proof over
+    /// `data_hash || salt`, so the pre-image is never submitted to the contract
+    /// and never appears in its events.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data_hash` - `sha256(data)`, computed by the prover
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid for `data_hash`
+    pub fn verify_hashed_data_proof(
+        env: Env,
+        proof: BytesN<32>,
+        data_hash: BytesN<32>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::verify_proof(env, proof, data_hash.into(), salt, hmac_key)
+    }
+
+    /// Verifies a proof like `verify_proof`, but only over canonical hex data:
+    /// an even number of lowercase hex digits (`[0-9a-f]`). Anything else
+    /// (uppercase digits, an odd length, a `0x` prefix) is rejected with
+    /// `InvalidEncoding` before hashing, so prover and verifier can't disagree
+    /// on the form of the same identifier.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The proven identifier, as lowercase hex
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if `data` is canonical hex and the proof is valid
+    pub fn verify_proof_canonical_hex(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let canonical = data.len().is_multiple_of(2)
+            && data.iter().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'));
+        if !canonical {
+            Self::publish_error(&env, VerificationError::InvalidEncoding);
+            return false;
+        }
+
+        Self::verify_proof(env, proof, data, salt, hmac_key)
+    }
+
+    /// Verifies a proof scoped to a caller-supplied context label (e.g. an order ID).
+    ///
+    /// The HMAC message is `data || salt || len(context) || context` (u32 big-endian
+    /// length), so a proof made for one context fails under any other.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `context` - The context label the prover bound into the proof
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid for this context
+    pub fn verify_proof_ctx(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        context: Bytes,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        Self::append_length_prefixed(&mut message, &context);
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "context");
+
+        is_valid
+    }
+
+    /// Returns this contract's address, which `verify_proof_bound_contract`
+    /// folds into the HMAC message.
+    pub fn contract_address(env: Env) -> Address {
+        env.current_contract_address()
+    }
+
+    /// Verifies a proof bound to this contract instance.
+    ///
+    /// The HMAC message is `data || salt || len(address) || address`, where
+    /// `address` is the XDR of `contract_address()` and the length is a u32
+    /// big-endian prefix. A proof made for one deployment fails on any other,
+    /// including a clone of the same code at a different address, without any
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid for this contract instance
+    pub fn verify_proof_bound_contract(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        Self::append_length_prefixed(&mut message, &env.current_contract_address().to_xdr(&env));
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "contract_bound");
+
+        is_valid
+    }
+
+    /// Verifies a proof over a member identifier and checks that the member is in
+    /// the on-chain membership set (see `add_member`).
+    ///
+    /// The HMAC message is `member || salt`. Both checks must pass: a valid proof
+    /// over a non-member, or a forged proof over a member, is rejected.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `member` - The member identifier the proof binds
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and `member` is in the set
+    pub fn verify_membership_proof(
+        env: Env,
+        proof: BytesN<32>,
+        member: BytesN<32>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let data = member.clone().into();
+        let authentic =
+            Self::verify_proof_with::<HmacSha256>(env.clone(), None, proof, data, salt, hmac_key, "membership");
+        if !authentic {
+            return false;
+        }
+
+        if !Self::is_member(env.clone(), member) {
+            Self::publish_error(&env, VerificationError::NotAMember);
+            return false;
+        }
+
+        true
+    }
+
+    /// Verifies an HMAC-authenticated Merkle root and the inclusion of one leaf
+    /// under it, so a prover can commit to many statements and reveal one.
+    ///
+    /// `root_proof` is a `verify_proof` proof over `root || salt`. Leaves hash as
+    /// `sha256(0x00 || leaf)` and inner nodes as `sha256(0x01 || left || right)`, so
+    /// an inner node can't be passed off as a leaf. `path` lists the siblings from
+    /// the leaf upwards; bit `i` of `index` is set when the running hash is the
+    /// right child at level `i`. Both checks must pass.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `root_proof` - The proof over the root (32 bytes HMAC-SHA256 output)
+    /// * `root` - The Merkle root the prover committed to
+    /// * `leaf` - The statement whose inclusion is proven
+    /// * `path` - The sibling hashes from the leaf level up to just below the root
+    /// * `index` - The leaf's position in the tree (less than `2^path.len()`)
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    ///
+    /// # Returns
+    /// * `true` if the root is authentic and `leaf` is included under it
+    pub fn verify_rooted_membership(
+        env: Env,
+        root_proof: BytesN<32>,
+        root: BytesN<32>,
+        leaf: Bytes,
+        path: Vec<BytesN<32>>,
+        index: u32,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let data = root.clone().into();
+        let authentic =
+            Self::verify_proof_with::<HmacSha256>(env.clone(), None, root_proof, data, salt, hmac_key, "merkle_root");
+        if !authentic {
+            return false;
+        }
+
+        let included = Self::merkle_root(&env, &leaf, &path, index)
+            .is_some_and(|computed| Self::secure_compare(&root, &computed));
+        if !included {
+            Self::publish_error(&env, VerificationError::MerkleMismatch);
+            return false;
+        }
+
+        true
+    }
+
+    /// Checks that a leaf is included under any of a set of Merkle roots, so an
+    /// inclusion proof keeps working across a root rotation.
+    ///
+    /// The leaf's root is computed once, as in `verify_rooted_membership`, and
+    /// compared against each candidate with `secure_compare`. The roots are taken
+    /// as already trusted; nothing here authenticates them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `leaf` - The statement whose inclusion is proven
+    /// * `path` - The sibling hashes from the leaf level up to just below the root
+    /// * `index` - The leaf's position in the tree (less than `2^path.len()`)
+    /// * `roots` - The currently accepted roots
+    ///
+    /// # Returns
+    /// * `true` if the path leads from `leaf` to one of `roots`
+    pub fn verify_membership_any_root(
+        env: Env,
+        leaf: Bytes,
+        path: Vec<BytesN<32>>,
+        index: u32,
+        roots: Vec<BytesN<32>>,
+    ) -> bool {
+        let included = Self::merkle_root(&env, &leaf, &path, index).is_some_and(|computed| {
+            roots.iter().any(|root| Self::secure_compare(&root, &computed))
+        });
+        if !included {
+            Self::publish_error(&env, VerificationError::MerkleMismatch);
+        }
+
+        included
+    }
+
+    /// Verifies a proof that embeds the ledger sequence at which it was generated,
+    /// rejecting proofs generated before `min_ledger` (anti-backdating).
+    ///
+    /// The HMAC message is `data || salt || embedded_ledger` (u32 big-endian), so the
+    /// embedded ledger is tamper-evident. This is a lower bound on the generation
+    /// ledger, not an age bound.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `embedded_ledger` - The ledger sequence bound into the proof by the prover
+    /// * `min_ledger` - The earliest acceptable generation ledger
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and `embedded_ledger >= min_ledger`
+    pub fn verify_proof_after(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        embedded_ledger: u32,
+        min_ledger: u32,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        if embedded_ledger < min_ledger {
+            Self::publish_error(&env, VerificationError::ProofBackdated);
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        message.extend_from_array(&embedded_ledger.to_be_bytes());
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "min_ledger");
+
+        is_valid
+    }
+
+    /// Verifies a proof that is only valid within `[not_before, expires_at]`,
+    /// checked against the ledger timestamp (inclusive on both ends).
+    ///
+    /// The HMAC message is `data || salt || not_before || expires_at` (u64 big-endian
+    /// each), so neither bound can be widened without invalidating the proof.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify (32 bytes HMAC-SHA256 output)
+    /// * `data` - The original data that was proven
+    /// * `salt` - The cryptographic salt used during proof generation (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (32 bytes)
+    /// * `not_before` - Unix timestamp at which the proof becomes valid
+    /// * `expires_at` - Unix timestamp after which the proof is no longer valid
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and `not_before <= now <= expires_at`
+    pub fn verify_proof_window(
+        env: Env,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        not_before: u64,
+        expires_at: u64,
+    ) -> bool {
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        if not_before > expires_at {
+            Self::publish_error(&env, VerificationError::InvalidWindow);
+            return false;
+        }
+
+        let now = env.ledger().timestamp();
+        if now < not_before {
+            Self::publish_error(&env, VerificationError::ProofNotYetValid);
+            return false;
+        }
+        if now > expires_at {
+            Self::publish_error(&env, VerificationError::ProofExpired);
+            return false;
+        }
+
+        let mut message = Self::build_message(&env, &data, &salt);
+        message.extend_from_array(&not_before.to_be_bytes());
+        message.extend_from_array(&expires_at.to_be_bytes());
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        Self::publish_result(&env, proof.as_ref(), is_valid, "time_window");
+
+        is_valid
+    }
+
+    /// Verifies a balance proof with additional balance check.
+    ///
+    /// The HMAC message is `len(balance_data) || balance_data || len(salt) || salt`
+    /// (u32 big-endian lengths), so the balance/salt boundary is unambiguous.
+    /// 
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required_amount_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// 
+    /// # Returns
+    /// * `true` if proof is valid and balance >= required_amount
+    pub fn verify_balance_proof(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_amount_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &balance_data) {
+            return false;
+        }
+
+        // First verify the proof itself
+        let proof_valid = Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key);
+
+        if !proof_valid {
+            return false;
+        }
+
+        // Parse and compare numeric values
+        let balance = Self::parse_decimal_to_scaled(&balance_data);
+        let required = Self::parse_decimal_to_scaled(&required_amount_data);
+
+        let balance_sufficient = match (balance, required) {
+            (Some(b), Some(r)) => b >= r,
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            balance_sufficient,
+        );
+
+        proof_valid && balance_sufficient
+    }
+
+    /// Verifies a balance proof like `verify_balance_proof`, optionally accepting
+    /// thousands separators (e.g. `"1,000,000.50"`) or a decimal comma
+    /// (e.g. `"1000,50"`) in the amounts.
+    ///
+    /// With `allow_separators`, `,` may appear only in the integer part and must
+    /// split it into groups of three digits (`"1,000.0"` parses like `"1000.0"`,
+    /// `"1,00.0"` is rejected). With `decimal_sep` set to `,`, the comma is the
+    /// decimal point instead: any `.` is rejected, and thousands separators can't
+    /// be enabled at the same time. The HMAC still binds the exact `balance_data`
+    /// bytes, so prover and verifier must agree on the exact formatting: a proof
+    /// over `"1,000.0"` does not verify against `"1000.0"`, nor `"1000,50"` against
+    /// `"1000.50"`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1,000.50")
+    /// * `required_amount_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `allow_separators` - Whether `,` thousands separators are accepted
+    /// * `decimal_sep` - The decimal separator character, `b'.'` or `b','`
+    ///
+    /// # Returns
+    /// * `true` if proof is valid, both amounts parse and balance >= required_amount
+    pub fn verify_balance_proof_fmt(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_amount_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        allow_separators: bool,
+        decimal_sep: u32,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &balance_data) {
+            return false;
+        }
+
+        if !Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key) {
+            return false;
+        }
+
+        // Out-of-range separators map to 0, which the parser rejects
+        let format = NumberFormat {
+            allow_separators,
+            decimal_sep: u8::try_from(decimal_sep).unwrap_or(0),
+        };
+        let balance = Self::parse_fixed_point_fmt(&balance_data, 8, format);
+        let required = Self::parse_fixed_point_fmt(&required_amount_data, 8, format);
+
+        let balance_sufficient = match (balance, required) {
+            (Some(b), Some(r)) => b >= r,
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            balance_sufficient,
+        );
+
+        balance_sufficient
+    }
+
+    /// Verifies a balance proof for a specific asset, comparing amounts at the
+    /// asset's configured decimal precision.
+    ///
+    /// The HMAC message is `asset_id || balance_data || salt`, so a proof for one
+    /// asset can't be presented for another.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `asset_id` - The asset identifier (decimals must be configured)
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required_amount_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if the asset is known, the proof is valid and balance >= required_amount
+    pub fn verify_asset_balance_proof(
+        env: Env,
+        proof: BytesN<32>,
+        asset_id: BytesN<32>,
+        balance_data: Bytes,
+        required_amount_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let decimals = match Self::asset_decimals(env.clone(), asset_id.clone()) {
+            Some(decimals) => decimals,
+            None => {
+                Self::publish_error(&env, VerificationError::UnknownAsset);
+                return false;
+            }
+        };
+
+        if !Self::verify_asset_hmac(&env, None, &proof, &asset_id, &balance_data, &salt, &hmac_key) {
+            return false;
+        }
+
+        let balance_sufficient = match (
+            Self::parse_fixed_point(&balance_data, decimals),
+            Self::parse_fixed_point(&required_amount_data, decimals),
+        ) {
+            (Some(b), Some(r)) => b >= r,
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            balance_sufficient,
+        );
+
+        balance_sufficient
+    }
+
+    /// Verifies an asset balance proof against the admin-configured threshold for
+    /// the asset (see `set_required_threshold`) instead of a caller-supplied amount.
+    ///
+    /// The proof uses the `verify_asset_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `asset_id` - The asset identifier (decimals and threshold must be configured)
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid and balance >= the stored threshold
+    pub fn verify_balance_against_threshold(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        asset_id: BytesN<32>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::check_balance_threshold(&env, None, &proof, &balance_data, &asset_id, &salt, &hmac_key)
+    }
+
+    /// Verifies a balance proof and returns the proven balance, so the caller gets
+    /// the trusted value from the same bytes the proof covered. The proof uses the
+    /// `verify_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `decimals` - Number of fractional digits the result is scaled by
+    ///
+    /// # Returns
+    /// * `Some(balance * 10^decimals)` if the proof is valid and the balance parses
+    /// * `None` otherwise
+    pub fn verify_and_extract_balance(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        decimals: u32,
+    ) -> Option<i128> {
+        if !Self::check_balance_present(&env, &balance_data) {
+            return None;
+        }
+
+        if !Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key) {
+            return None;
+        }
+
+        let balance = Self::parse_fixed_point(&balance_data, decimals);
+        if balance.is_none() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+        }
+        balance
+    }
+
+    /// Verifies a balance proof against a required amount and returns how far the
+    /// balance exceeds it. The proof uses the `verify_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `decimals` - Number of fractional digits the margin is scaled by
+    ///
+    /// # Returns
+    /// * `Some((balance - required) * 10^decimals)` if the proof is valid and
+    ///   balance >= required (zero at the exact threshold)
+    /// * `None` otherwise
+    pub fn verify_balance_margin(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        decimals: u32,
+    ) -> Option<i128> {
+        let balance = Self::verify_and_extract_balance(
+            env.clone(),
+            proof,
+            balance_data,
+            salt,
+            hmac_key,
+            decimals,
+        )?;
+
+        let margin = match Self::parse_fixed_point(&required_data, decimals)
+            .and_then(|required| Self::checked_sub_fp(balance, required))
+        {
+            Some(margin) => margin,
+            None => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return None;
+            }
+        };
+
+        let sufficient = margin >= 0;
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            sufficient,
+        );
+
+        sufficient.then_some(margin)
+    }
+
+    /// Verifies a balance proof against a required amount given directly as a
+    /// fixed-point integer, so callers holding a numeric amount don't have to
+    /// format it as a string. The proof uses the `verify_balance_proof` message
+    /// layout; only `balance_data` is parsed.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required` - The required amount, scaled by `10^decimals`
+    /// * `op` - The comparison applied as `balance op required`
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `decimals` - Number of fractional digits `required` is scaled by
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid, the balance parses and the comparison holds
+    pub fn verify_balance_proof_i128(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required: i128,
+        op: CompareOp,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        decimals: u32,
+    ) -> bool {
+        let balance = match Self::verify_and_extract_balance(
+            env.clone(),
+            proof,
+            balance_data,
+            salt,
+            hmac_key,
+            decimals,
+        ) {
+            Some(balance) => balance,
+            None => return false,
+        };
+
+        let result = Self::compare(op, balance, required);
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            result,
+        );
+
+        result
+    }
+
+    /// Verifies a balance proof and reports authenticity and the threshold check
+    /// separately, so a forged proof can be told apart from an honest balance
+    /// that falls short. The proof uses the `verify_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `op` - The comparison applied as `balance op required`
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `decimals` - Number of fractional digits both amounts are compared at
+    ///
+    /// # Returns
+    /// * `(hmac_valid, threshold_met)`; `threshold_met` is always `false` when
+    ///   `hmac_valid` is `false`, and is only meaningful when it is `true`
+    pub fn verify_balance_detailed(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_data: Bytes,
+        op: CompareOp,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        decimals: u32,
+    ) -> (bool, bool) {
+        let hmac_valid = Self::check_balance_present(&env, &balance_data)
+            && Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key);
+        if !hmac_valid {
+            return (false, false);
+        }
+
+        let threshold_met = match (
+            Self::parse_fixed_point(&balance_data, decimals),
+            Self::parse_fixed_point(&required_data, decimals),
+        ) {
+            (Some(balance), Some(required)) => Self::compare(op, balance, required),
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            threshold_met,
+        );
+
+        (true, threshold_met)
+    }
+
+    /// Verifies a balance proof that binds the ledger the balance was observed at,
+    /// checking authenticity, freshness and the threshold together.
+    ///
+    /// The HMAC message is the `verify_balance_proof` layout followed by
+    /// `as_of_ledger` (u32 big-endian). The proof is stale (`ProofExpired`) once
+    /// more than `max_age` ledgers have passed since `as_of_ledger`; an
+    /// `as_of_ledger` ahead of the current ledger is rejected with
+    /// `LedgerInFuture`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `required_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `op` - The comparison applied as `balance op required`
+    /// * `as_of_ledger` - The ledger sequence the balance was observed at
+    /// * `max_age` - The maximum number of ledgers since `as_of_ledger`
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    /// * `decimals` - Number of fractional digits both amounts are compared at
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid, fresh and the comparison holds
+    pub fn verify_fresh_balance(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_data: Bytes,
+        op: CompareOp,
+        as_of_ledger: u32,
+        max_age: u32,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        decimals: u32,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &balance_data)
+            || !Self::validate_inputs(&env, &balance_data, &salt)
+        {
+            return false;
+        }
+
+        let mut message = Self::domain_prefix(&env);
+        Self::append_length_prefixed(&mut message, &balance_data);
+        Self::append_length_prefixed(&mut message, &salt);
+        message.extend_from_array(&as_of_ledger.to_be_bytes());
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+        Self::publish_result(&env, proof.as_ref(), is_valid, "balance");
+        if !is_valid {
+            return false;
+        }
+
+        match ledger_gap(env.ledger().sequence(), as_of_ledger) {
+            Some(age) if age <= max_age => {}
+            Some(_) => {
+                Self::publish_error(&env, VerificationError::ProofExpired);
+                return false;
+            }
+            None => {
+                Self::publish_error(&env, VerificationError::LedgerInFuture);
+                return false;
+            }
+        }
+
+        let result = match (
+            Self::parse_fixed_point(&balance_data, decimals),
+            Self::parse_fixed_point(&required_data, decimals),
+        ) {
+            (Some(balance), Some(required)) => Self::compare(op, balance, required),
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            result,
+        );
+
+        result
+    }
+
+    /// Verifies a KYC tier: a proof binding an account's balance and the account
+    /// itself, a balance within `[min, max]`, and the account's Merkle membership
+    /// under the approved-accounts root. All three must hold.
+    ///
+    /// The HMAC message is `len(balance_data) || balance_data || account ||
+    /// len(salt) || salt` (u32 big-endian lengths). The Merkle path uses the
+    /// `verify_rooted_membership` layout with `account` as the leaf.
+    /// `approved_root` is not authenticated here, so callers must pass the root
+    /// they trust (e.g. from their own storage), not one supplied by the prover.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `min_data` - The tier's lower bound, inclusive (decimal string)
+    /// * `max_data` - The tier's upper bound, inclusive (decimal string)
+    /// * `account` - The account identifier bound into the proof
+    /// * `approved_root` - The trusted root of the approved-accounts tree
+    /// * `account_path` - The sibling hashes from `account` up to just below the root
+    /// * `account_index` - The account's position in the tree
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid, the balance is in the tier and the account
+    ///   is approved
+    pub fn verify_kyc_tier(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        min_data: Bytes,
+        max_data: Bytes,
+        account: BytesN<32>,
+        approved_root: BytesN<32>,
+        account_path: Vec<BytesN<32>>,
+        account_index: u32,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &balance_data)
+            || !Self::validate_inputs(&env, &balance_data, &salt)
+        {
+            return false;
+        }
+
+        let mut message = Self::domain_prefix(&env);
+        Self::append_length_prefixed(&mut message, &balance_data);
+        message.append(&account.clone().into());
+        Self::append_length_prefixed(&mut message, &salt);
+
+        let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+        Self::publish_result(&env, proof.as_ref(), is_valid, "kyc_tier");
+        if !is_valid {
+            return false;
+        }
+
+        let in_tier = match (
+            Self::parse_decimal_to_scaled(&balance_data),
+            Self::parse_decimal_to_scaled(&min_data),
+            Self::parse_decimal_to_scaled(&max_data),
+        ) {
+            (Some(_), Some(min), Some(max)) if min > max => {
+                Self::publish_error(&env, VerificationError::InvalidRange);
+                return false;
+            }
+            (Some(balance), Some(min), Some(max)) => min <= balance && balance <= max,
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return false;
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            in_tier,
+        );
+        if !in_tier {
+            return false;
+        }
+
+        let approved = Self::merkle_root(&env, &account.into(), &account_path, account_index)
+            .is_some_and(|computed| Self::secure_compare(&approved_root, &computed));
+        if !approved {
+            Self::publish_error(&env, VerificationError::MerkleMismatch);
+            return false;
+        }
+
+        true
+    }
+
+    /// Verifies a balance proof for an indivisible asset, where both amounts must
+    /// be whole numbers. The proof uses the `verify_balance_proof` message layout.
+    ///
+    /// An amount containing a decimal point (even `"10.0"`) is rejected with
+    /// `UnexpectedDecimal` rather than truncated.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (integer string, e.g., "10")
+    /// * `required_data` - The required amount as bytes (integer string, e.g., "3")
+    /// * `op` - The comparison applied as `balance op required`
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if the proof is valid, both amounts are integers and the
+    ///   comparison holds
+    pub fn verify_integer_balance_proof(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        required_data: Bytes,
+        op: CompareOp,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &balance_data)
+            || !Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key)
+        {
+            return false;
+        }
+
+        let result = match (
+            Self::parse_integer(&env, &balance_data),
+            Self::parse_integer(&env, &required_data),
+        ) {
+            (Some(balance), Some(required)) => Self::compare(op, balance, required),
+            _ => false,
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            result,
+        );
+
+        result
+    }
+
+    /// Verifies a balance proof and returns which tier the balance falls into.
+    /// The proof uses the `verify_balance_proof` message layout.
+    ///
+    /// `tier_bounds` are strictly ascending lower bounds: tier `i` is
+    /// `[tier_bounds[i], tier_bounds[i + 1])` and the last tier is unbounded above.
+    /// Only the tier index is returned or published; note the balance itself is
+    /// still part of the call's arguments.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The proof hash to verify
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `tier_bounds` - Ascending tier lower bounds (decimal strings)
+    /// * `salt` - The cryptographic salt
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `Some(tier_index)` if the proof is valid and the balance is at least the
+    ///   lowest bound
+    /// * `None` otherwise, including when the bounds don't parse or don't ascend
+    pub fn verify_balance_tier(
+        env: Env,
+        proof: BytesN<32>,
+        balance_data: Bytes,
+        tier_bounds: Vec<Bytes>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> Option<u32> {
+        if !Self::check_balance_present(&env, &balance_data)
+            || !Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key)
+        {
+            return None;
+        }
+
+        let balance = match Self::parse_decimal_to_scaled(&balance_data) {
+            Some(balance) => balance,
+            None => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return None;
+            }
+        };
+
+        let mut tier = None;
+        let mut prev_bound = None;
+        for i in 0..tier_bounds.len() {
+            let bound = match Self::parse_decimal_to_scaled(&tier_bounds.get(i).unwrap()) {
+                Some(bound) if prev_bound.is_none_or(|prev| bound > prev) => bound,
+                _ => {
+                    Self::publish_error(&env, VerificationError::InvalidInput);
+                    return None;
+                }
+            };
+            if balance >= bound {
+                tier = Some(i);
+            }
+            prev_bound = Some(bound);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_tier"),),
+            tier,
+        );
+
+        tier
+    }
+
+    /// Verifies two balance proofs and checks that balance A is strictly greater
+    /// than balance B, without either amount being compared off-chain.
+    ///
+    /// Both proofs use the `verify_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof_a` - The proof over `data_a`
+    /// * `data_a` - Balance A as bytes (decimal string, e.g., "1000.50")
+    /// * `proof_b` - The proof over `data_b`
+    /// * `data_b` - Balance B as bytes (decimal string, e.g., "500.25")
+    /// * `salt` - The cryptographic salt used for both proofs
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if both proofs are valid, both balances parse and A > B
+    pub fn verify_balance_greater(
+        env: Env,
+        proof_a: BytesN<32>,
+        data_a: Bytes,
+        proof_b: BytesN<32>,
+        data_b: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &data_a)
+            || !Self::check_balance_present(&env, &data_b)
+            || !Self::verify_balance_hmac(&env, &proof_a, &data_a, &salt, &hmac_key)
+            || !Self::verify_balance_hmac(&env, &proof_b, &data_b, &salt, &hmac_key)
+        {
+            return false;
+        }
+
+        let greater = match (
+            Self::parse_decimal_to_scaled(&data_a),
+            Self::parse_decimal_to_scaled(&data_b),
+        ) {
+            (Some(a), Some(b)) => a > b,
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            greater,
+        );
+
+        greater
+    }
+
+    /// Verifies a snapshot and a current balance proof and checks the balance
+    /// strictly increased since the snapshot. An unchanged balance fails.
+    ///
+    /// Both proofs use the `verify_balance_proof` message layout, as in
+    /// `verify_balance_greater`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `snapshot_proof` - The proof over `snapshot_data`
+    /// * `snapshot_data` - The earlier balance (decimal string, e.g., "1000.50")
+    /// * `current_proof` - The proof over `current_data`
+    /// * `current_data` - The current balance (decimal string, e.g., "1200.00")
+    /// * `salt` - The cryptographic salt used for both proofs
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if both proofs are valid, both balances parse and current > snapshot
+    pub fn verify_balance_increased(
+        env: Env,
+        snapshot_proof: BytesN<32>,
+        snapshot_data: Bytes,
+        current_proof: BytesN<32>,
+        current_data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        Self::verify_balance_greater(
+            env,
+            current_proof,
+            current_data,
+            snapshot_proof,
+            snapshot_data,
+            salt,
+            hmac_key,
+        )
+    }
+
+    /// Verifies two balance proofs and checks the balances are within
+    /// `tolerance_percent` of each other: `|a - b| * 100 <= max(a, b) * tolerance_percent`.
+    ///
+    /// Two zero balances are within any spread. Negative balances are rejected, and
+    /// arithmetic overflow rejects the check rather than wrapping. Both proofs use
+    /// the `verify_balance_proof` message layout.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof_a` - The proof over `data_a`
+    /// * `data_a` - Balance A as bytes (decimal string, e.g., "1000.50")
+    /// * `proof_b` - The proof over `data_b`
+    /// * `data_b` - Balance B as bytes (decimal string, e.g., "990.25")
+    /// * `tolerance_percent` - Maximum spread, as a percentage of the larger balance
+    /// * `salt` - The cryptographic salt used for both proofs
+    /// * `hmac_key` - The HMAC secret key
+    ///
+    /// # Returns
+    /// * `true` if both proofs are valid and the balances are within the spread
+    pub fn verify_within_spread(
+        env: Env,
+        proof_a: BytesN<32>,
+        data_a: Bytes,
+        proof_b: BytesN<32>,
+        data_b: Bytes,
+        tolerance_percent: u32,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(&env, &data_a)
+            || !Self::check_balance_present(&env, &data_b)
+            || !Self::verify_balance_hmac(&env, &proof_a, &data_a, &salt, &hmac_key)
+            || !Self::verify_balance_hmac(&env, &proof_b, &data_b, &salt, &hmac_key)
+        {
+            return false;
+        }
+
+        let within = match (
+            Self::parse_decimal_to_scaled(&data_a),
+            Self::parse_decimal_to_scaled(&data_b),
+        ) {
+            (Some(a), Some(b)) if a >= 0 && b >= 0 => {
+                Self::checked_sub_fp(a.max(b), a.min(b))
+                    .and_then(|spread| Self::checked_mul_fp(spread, 100))
+                    .zip(Self::checked_mul_fp(a.max(b), tolerance_percent as i128))
+                    .map(|(spread, allowed)| spread <= allowed)
+            }
+            _ => None,
+        };
+
+        let within = match within {
+            Some(within) => within,
+            None => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            within,
+        );
+
+        within
+    }
+
+    /// Verifies a proof of solvency: every per-account balance proof is valid and
+    /// the sum of the proven balances covers the liabilities.
+    ///
+    /// Each proof uses the `verify_balance_proof` message layout. Balances are
+    /// summed with overflow-checked addition; an overflow rejects the batch.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of per-account balance proofs
+    /// * `balance_items` - Vector of balances (decimal strings) for each proof
+    /// * `salts` - Vector of salts for each proof
+    /// * `liabilities_data` - Total liabilities as bytes (decimal string)
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * `true` if all proofs are valid and sum(balances) >= liabilities
+    pub fn verify_solvency(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        balance_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        liabilities_data: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &balance_items) {
+            return false;
+        }
+
+        let count = proofs.len();
+
+        if count == 0 || count != balance_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return false;
+        }
+
+        let mut total: i128 = 0;
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let balance_data = balance_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+
+            if !Self::check_balance_present(&env, &balance_data)
+                || !Self::verify_balance_hmac(&env, &proof, &balance_data, &salt, &hmac_key)
+            {
+                return false;
+            }
+
+            let sum = Self::parse_decimal_to_scaled(&balance_data)
+                .and_then(|balance| Self::checked_add_fp(total, balance));
+            total = match sum {
+                Some(sum) => sum,
+                None => {
+                    Self::publish_error(&env, VerificationError::InvalidInput);
+                    return false;
+                }
+            };
+        }
+
+        let solvent = match Self::parse_decimal_to_scaled(&liabilities_data) {
+            Some(liabilities) => total >= liabilities,
+            None => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "solvency_check"),),
+            solvent,
+        );
+
+        solvent
+    }
+
+    /// Opens a set of additive value commitments and checks that the committed
+    /// values sum to `opening_sum` and that the sum covers `required`.
+    ///
+    /// Each commitment is 48 bytes, `masked || tag`, for a value `v` (scaled by
+    /// 10^8, as a 16-byte big-endian `i128`) and randomness `r`:
+    /// * `masked = v XOR sha256(0x00 || r)[..16]`
+    /// * `tag = sha256(0x01 || v || r)`
+    ///
+    /// Opening unmasks `v` with `r` and checks the tag, so a commitment opens to
+    /// exactly one value. This lets provers publish per-account commitments ahead
+    /// of time and later prove only their aggregate against a threshold.
+    ///
+    /// # Limitations
+    /// This is not a homomorphic commitment: commitments can't be added without
+    /// opening them, and opening passes every `r` in the transaction, so anyone
+    /// observing it can recover each individual value. Commitments only hide
+    /// values until they are opened. There is no zero-knowledge property and no
+    /// binding to an account; pair commitments with account proofs where that
+    /// matters.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `commitments` - The 48-byte commitments to open
+    /// * `opening_sum` - The claimed sum of the committed values (decimal string)
+    /// * `opening_randomness` - The randomness for each commitment
+    /// * `required` - The threshold the sum must meet (decimal string)
+    ///
+    /// # Returns
+    /// * `true` if every commitment opens, the values sum to `opening_sum`, and
+    ///   the sum is at least `required`
+    pub fn verify_committed_sum(
+        env: Env,
+        commitments: Vec<Bytes>,
+        opening_sum: Bytes,
+        opening_randomness: Vec<Bytes>,
+        required: Bytes,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &commitments) {
+            return false;
+        }
+
+        let count = commitments.len();
+
+        if count == 0 || count != opening_randomness.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return false;
+        }
+
+        let (claimed, required) = match (
+            Self::parse_decimal_to_scaled(&opening_sum),
+            Self::parse_decimal_to_scaled(&required),
+        ) {
+            (Some(claimed), Some(required)) => (claimed, required),
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return false;
+            }
+        };
+
+        let mut total: i128 = 0;
+        for i in 0..count {
+            let commitment = commitments.get(i).unwrap();
+            let randomness = opening_randomness.get(i).unwrap();
+
+            if commitment.len() != COMMITMENT_LEN {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return false;
+            }
+
+            let value = match Self::open_commitment(&env, &commitment, &randomness) {
+                Some(value) => value,
+                None => {
+                    Self::publish_error(&env, VerificationError::InvalidProof);
+                    return false;
+                }
+            };
+
+            total = match Self::checked_add_fp(total, value) {
+                Some(sum) => sum,
+                None => {
+                    Self::publish_error(&env, VerificationError::InvalidInput);
+                    return false;
+                }
+            };
+        }
+
+        if total != claimed {
+            Self::publish_error(&env, VerificationError::InvalidProof);
+            return false;
+        }
+
+        let covered = total >= required;
+
+        env.events().publish(
+            (Symbol::new(&env, "committed_sum_check"),),
+            covered,
+        );
+
+        covered
+    }
+
+    /// Stores a balance commitment for a later `reveal_balance`.
+    ///
+    /// The commitment is `sha256(balance_data || blinding)`. Committing first and
+    /// revealing at least `MIN_REVEAL_GAP` ledgers later prevents front-running of
+    /// the reveal. A new commitment replaces any pending one.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `account` - The committing account (must authorize the call)
+    /// * `commitment` - `sha256(balance_data || blinding)`
+    pub fn commit_balance(env: Env, account: Address, commitment: BytesN<32>) {
+        account.require_auth();
+
+        let record = BalanceCommitment {
+            commitment,
+            ledger: env.ledger().sequence(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::BalanceCommitment(account.clone()), &record);
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_committed"),),
+            (account, record.ledger),
+        );
+    }
+
+    /// Reveals a committed balance and compares it against a required amount.
+    ///
+    /// The pending commitment is consumed when it opens correctly, whatever the
+    /// outcome of the comparison.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `account` - The account that made the commitment
+    /// * `balance_data` - The balance value as bytes (decimal string, e.g., "1000.50")
+    /// * `blinding` - The 32-byte blinding factor used in the commitment
+    /// * `required_data` - The required amount as bytes (decimal string, e.g., "500.25")
+    /// * `op` - The comparison applied as `balance op required`
+    ///
+    /// # Returns
+    /// * `true` if the commitment opens, the gap has elapsed and the comparison holds
+    pub fn reveal_balance(
+        env: Env,
+        account: Address,
+        balance_data: Bytes,
+        blinding: BytesN<32>,
+        required_data: Bytes,
+        op: CompareOp,
+    ) -> bool {
+        let key = DataKey::BalanceCommitment(account);
+        let record: BalanceCommitment = match env.storage().persistent().get(&key) {
+            Some(record) => record,
+            None => {
+                Self::publish_error(&env, VerificationError::MissingCommitment);
+                return false;
+            }
+        };
+
+        match ledger_gap(env.ledger().sequence(), record.ledger) {
+            Some(gap) if gap >= MIN_REVEAL_GAP => {}
+            Some(_) => {
+                Self::publish_error(&env, VerificationError::RevealTooEarly);
+                return false;
+            }
+            None => {
+                Self::publish_error(&env, VerificationError::LedgerInFuture);
+                return false;
+            }
+        }
+
+        let mut opening = Bytes::new(&env);
+        opening.append(&balance_data);
+        opening.append(&blinding.into());
+        let computed: BytesN<32> = env.crypto().sha256(&opening).into();
+
+        if !Self::secure_compare(&record.commitment, &computed) {
+            Self::publish_error(&env, VerificationError::CommitmentMismatch);
+            return false;
+        }
+
+        env.storage().persistent().remove(&key);
+
+        let balance = Self::parse_decimal_to_scaled(&balance_data);
+        let required = Self::parse_decimal_to_scaled(&required_data);
+
+        let result = match (balance, required) {
+            (Some(b), Some(r)) => Self::compare(op, b, r),
+            _ => {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(&env, "balance_check"),),
+            result,
+        );
+
+        result
+    }
+
+    /// Formats a fixed-point amount as the canonical decimal string that balance
+    /// proofs bind to. This is the inverse of the contract's fixed-point parser.
+    ///
+    /// The canonical form has no leading zeros in the integer part and no trailing
+    /// zeros in the fractional part, except that a zero fraction is written as `.0`
+    /// (e.g. `10_000_000_000` at 7 decimals is `"1000.0"`). With `decimals == 0`
+    /// no decimal point is written. Formatting a parsed string therefore always
+    /// yields the same bytes, whatever zero padding the original string used.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - The amount scaled by `10^decimals` (e.g. stroops for `decimals = 7`)
+    /// * `decimals` - The number of fractional digits in `amount`
+    pub fn format_fixed_point(env: Env, amount: i128, decimals: u32) -> Bytes {
+        // Decimal digits of |amount|, most significant first (u128 has at most 39)
+        let mut digits = [0u8; 39];
+        let mut len: u32 = 0;
+        let mut magnitude = amount.unsigned_abs();
+        loop {
+            digits[len as usize] = b'0' + (magnitude % 10) as u8;
+            len += 1;
+            magnitude /= 10;
+            if magnitude == 0 {
+                break;
+            }
+        }
+        digits[..len as usize].reverse();
+
+        let mut out = Bytes::new(&env);
+        if amount < 0 {
+            out.push_back(b'-');
+        }
+
+        // Integer part
+        if len > decimals {
+            out.extend_from_slice(&digits[..(len - decimals) as usize]);
+        } else {
+            out.push_back(b'0');
+        }
+
+        if decimals == 0 {
+            return out;
+        }
+
+        // Fractional part: left-pad with zeros to `decimals` digits, then drop
+        // trailing zeros (keeping at least one digit)
+        out.push_back(b'.');
+        let frac_start = out.len();
+        for _ in len..decimals {
+            out.push_back(b'0');
+        }
+        let from = len.saturating_sub(decimals);
+        out.extend_from_slice(&digits[from as usize..len as usize]);
+
+        let mut end = out.len();
+        while end > frac_start + 1 && out.get(end - 1) == Some(b'0') {
+            end -= 1;
+        }
+        out.slice(..end)
+    }
+
+    /// Parses an integer-only amount, publishing `UnexpectedDecimal` if it has a
+    /// decimal point and `InvalidInput` if it otherwise fails to parse.
+    fn parse_integer(env: &Env, data: &Bytes) -> Option<i128> {
+        if data.iter().any(|byte| byte == b'.') {
+            Self::publish_error(env, VerificationError::UnexpectedDecimal);
+            return None;
+        }
+
+        let value = Self::parse_fixed_point(data, 0);
+        if value.is_none() {
+            Self::publish_error(env, VerificationError::InvalidInput);
+        }
+        value
+    }
+
+    /// Parses a decimal string (e.g., "1234.56") to a scaled integer for comparison.
+    /// Returns None if parsing fails or if no digits are present.
+    /// The result is scaled by 10^8 to handle up to 8 decimal places.
+    fn parse_decimal_to_scaled(data: &Bytes) -> Option<i128> {
+        Self::parse_fixed_point(data, 8)
+    }
+
+    /// Parses a decimal string (e.g., "1234.56") to an integer scaled by `10^decimals`.
+    /// Returns None if parsing fails, if no digits are present or on overflow.
+    /// Fractional digits beyond `decimals` are truncated.
+    fn parse_fixed_point(data: &Bytes, decimals: u32) -> Option<i128> {
+        Self::parse_fixed_point_fmt(data, decimals, NumberFormat::default())
+    }
+
+    /// `parse_fixed_point` with additional accepted formatting.
+    fn parse_fixed_point_fmt(data: &Bytes, decimals: u32, format: NumberFormat) -> Option<i128> {
+        // Return None for empty input
+        if data.is_empty() {
+            return None;
+        }
+
+        // Only `.` and `,` decimals; with a `,` decimal there is no thousands
+        // separator, and a `.` anywhere is rejected as an invalid character
+        match format.decimal_sep {
+            b'.' => {}
+            b',' if !format.allow_separators => {}
+            _ => return None,
+        }
+
+        let mut result: i128 = 0;
+        let mut decimal_places: u32 = 0;
+        let mut found_decimal = false;
+        let mut is_negative = false;
+        let mut has_digits = false; // Track if at least one digit was parsed
+        let mut found_separator = false;
+        let mut group_len: u32 = 0; // Integer digits since the last separator
+
+        for i in 0..data.len() {
+            let byte = data.get(i)?;
+            
+            // Handle negative sign (only at the start, before any digits)
+            if byte == b'-' && !has_digits && !found_decimal {
+                is_negative = true;
+                continue;
+            }
+
+            // Handle decimal point
+            if byte == format.decimal_sep {
+                if found_decimal {
+                    return None; // Multiple decimal points
+                }
+                if found_separator && group_len != 3 {
+                    return None; // Short last group, e.g. "1,00.0"
+                }
+                found_decimal = true;
+                continue;
+            }
+
+            // Handle thousands separator: integer part only, first group of 1-3
+            // digits, every later group exactly 3
+            if byte == b',' {
+                if !format.allow_separators || found_decimal || !has_digits {
+                    return None;
+                }
+                if group_len > 3 || (found_separator && group_len != 3) {
+                    return None;
+                }
+                found_separator = true;
+                group_len = 0;
+                continue;
+            }
+
+            // Handle digits
+            if byte.is_ascii_digit() {
+                has_digits = true;
+
+                if found_decimal {
+                    if decimal_places == decimals {
+                        // Too many decimal places, truncate
+                        continue;
+                    }
+                    decimal_places += 1;
+                } else {
+                    group_len += 1;
+                }
+
+                let digit = (byte - b'0') as i128;
+                result = Self::checked_add_fp(Self::checked_mul_fp(result, 10)?, digit)?;
+            } else if byte != b' ' {
+                // Invalid character (allow spaces to be ignored)
+                return None;
+            }
+        }
+
+        // Must have at least one digit to be valid
+        if !has_digits {
+            return None;
+        }
+
+        // A trailing separator or short last group without a fraction, e.g. "1,00"
+        if found_separator && !found_decimal && group_len != 3 {
+            return None;
+        }
+
+        // Scale to `decimals` places for consistent comparison
+        for _ in decimal_places..decimals {
+            result = Self::checked_mul_fp(result, 10)?;
+        }
+
+        if is_negative {
+            result = Self::checked_sub_fp(0, result)?;
+        }
+
+        Some(result)
+    }
+
+    /// Batch verification of multiple proofs for efficiency.
+    /// 
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    /// 
+    /// # Returns
+    /// * `true` if ALL proofs are valid, `false` if any proof is invalid (or, with
+    ///   `unique_salts` enabled, if any two items share a salt)
+    pub fn verify_batch(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let unique_salts = Self::unique_salts(env.clone());
+        Self::verify_batch_items(env, proofs, data_items, salts, hmac_key, unique_salts)
+    }
+
+    /// Shared body of `verify_batch` and `verify_batch_ordered`.
+    fn verify_batch_items(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+        unique_salts: bool,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &data_items) {
+            return false;
+        }
+
+        let count = proofs.len();
+        
+        if count != data_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return false;
+        }
+
+        if unique_salts && Self::find_reused_salt(&env, &salts).is_some() {
+            return false;
+        }
+
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let data = data_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+
+            if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key.clone()) {
+                env.events().publish(
+                    (Symbol::new(&env, "batch_failed_at"),),
+                    i,
+                );
+                return false;
+            }
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "batch_verified"),),
+            count,
+        );
+
+        true
+    }
+
+    /// Verifies a batch and consumes every proof in it, all or nothing: the replay
+    /// records (see `verify_proof_once`) are written only once every proof has
+    /// verified and none is already consumed.
+    ///
+    /// A batch with an invalid proof, a proof still within its replay window, or
+    /// the same proof twice is rejected without consuming any of its proofs; a
+    /// replayed proof is reported as `ProofReplayed` with its index in a
+    /// `batch_failed_at` event.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify and consume
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * `true` if every proof was valid and unconsumed (and, with `unique_salts`
+    ///   enabled, every salt distinct), and all are now consumed
+    pub fn verify_batch_consume(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &data_items) {
+            return false;
+        }
+
+        let count = proofs.len();
+        if count != data_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return false;
+        }
+
+        if Self::unique_salts(env.clone()) && Self::find_reused_salt(&env, &salts).is_some() {
+            return false;
+        }
+
+        let now = env.ledger().sequence();
+        let storage = env.storage().persistent();
+        let mut keys: Vec<DataKey> = Vec::new(&env);
+
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let key = DataKey::ConsumedProof(Self::proof_id(env.clone(), proof.clone()));
+
+            let evict_at: Option<u32> = storage.get(&key);
+            if evict_at.is_some_and(|evict_at| now < evict_at) || keys.contains(&key) {
+                Self::publish_error(&env, VerificationError::ProofReplayed);
+                env.events().publish((Symbol::new(&env, "batch_failed_at"),), i);
+                return false;
+            }
+
+            let data = data_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+            if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key.clone()) {
+                env.events().publish((Symbol::new(&env, "batch_failed_at"),), i);
+                return false;
+            }
+
+            keys.push_back(key);
+        }
+
+        // Every proof passed: only now record them all
+        for key in keys.iter() {
+            storage.set(&key, &now.saturating_add(REPLAY_WINDOW));
+            storage.extend_ttl(&key, REPLAY_WINDOW, REPLAY_WINDOW);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "batch_verified"),),
+            count,
+        );
+
+        true
+    }
+
+    /// Verifies a batch like `verify_batch`, additionally requiring `data_items` to
+    /// be in strictly ascending order: lexicographic by unsigned byte value, as in
+    /// `compare_bytes`, with a proper prefix sorting first.
+    ///
+    /// For batches whose items are meant to follow a fixed order (e.g. sorted
+    /// account IDs), this binds each position to its content: a shuffled or
+    /// duplicated item is rejected with `InvalidInput` before any proof is checked.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Data items corresponding to each proof, strictly ascending
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * `true` if the data is strictly ascending and ALL proofs are valid (and,
+    ///   with `unique_salts` enabled, every salt is distinct)
+    pub fn verify_batch_ordered(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        if !Self::check_batch_size(&env, &data_items) {
+            return false;
+        }
+
+        for i in 1..data_items.len() {
+            let prev = data_items.get(i - 1).unwrap();
+            let item = data_items.get(i).unwrap();
+            if prev.iter().cmp(item.iter()) != Ordering::Less {
+                Self::publish_error(&env, VerificationError::InvalidInput);
+                return false;
+            }
+        }
+
+        let unique_salts = Self::unique_salts(env.clone());
+        Self::verify_batch_items(env, proofs, data_items, salts, hmac_key, unique_salts)
+    }
+
+    /// Counts how many proofs in a batch verify successfully.
+    ///
+    /// Unlike `verify_batch`, every item is checked; the result is only the number
+    /// of valid items, which is cheaper to return than a per-item result vector.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * The number of valid proofs, or `0` if the input vectors differ in length
+    ///   or `unique_salts` is enabled and a salt is reused
+    pub fn verify_batch_count(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> u32 {
+        if !Self::check_batch_size(&env, &data_items) {
+            return 0;
+        }
+
+        let count = proofs.len();
+
+        if count != data_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return 0;
+        }
+
+        if Self::unique_salts(env.clone()) && Self::find_reused_salt(&env, &salts).is_some() {
+            return 0;
+        }
+
+        let mut valid: u32 = 0;
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let data = data_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+
+            if Self::verify_proof(env.clone(), proof, data, salt, hmac_key.clone()) {
+                valid += 1;
+            }
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "batch_valid_count"),),
+            (valid, count),
+        );
+
+        valid
+    }
+
+    /// Verifies every proof in a batch and returns why each item failed.
+    ///
+    /// Every item is checked, without stopping at the first failure, so a batch
+    /// with mixed failure modes (short salts, bad HMACs) can be triaged in one call.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * One reason per item, `VerificationError::None` for a valid proof, or an
+    ///   empty vector if the input vectors differ in length. With `unique_salts`
+    ///   enabled, an item whose salt an earlier item already used is reported as
+    ///   `DuplicateSalt` without checking its proof.
+    pub fn verify_batch_reasons(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> Vec<VerificationError> {
+        if !Self::check_batch_size(&env, &data_items) {
+            return Vec::new(&env);
+        }
+
+        let count = proofs.len();
+        let mut reasons = Vec::new(&env);
+
+        if count != data_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return reasons;
+        }
+
+        let unique_salts = Self::unique_salts(env.clone());
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let data = data_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+
+            if unique_salts && Self::salt_reused_at(&salts, i) {
+                Self::publish_error(&env, VerificationError::DuplicateSalt);
+                reasons.push_back(VerificationError::DuplicateSalt);
+                continue;
+            }
+
+            reasons.push_back(Self::proof_reason(&env, &proof, &data, &salt, &hmac_key));
+        }
+
+        reasons
+    }
+
+    /// Verifies a batch of proofs that share one salt, returning a result per item.
+    ///
+    /// The salt length is validated once for the whole batch; each item is then
+    /// checked like `verify_proof` with that salt, without stopping at a failure.
+    /// Since the salt is shared by design, there is no `unique_salts` option.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salt` - The salt every proof was generated with (16 bytes minimum)
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * Whether each proof is valid, or an empty vector if the salt is too short,
+    ///   the batch is too large or the input vectors differ in length
+    pub fn verify_batch_shared_salt(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> Vec<bool> {
+        let mut results = Vec::new(&env);
+
+        if !Self::check_batch_size(&env, &data_items) {
+            return results;
+        }
+
+        if proofs.len() != data_items.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return results;
+        }
+
+        if salt.len() < MIN_SALT_LEN {
+            Self::publish_error(&env, VerificationError::InvalidSaltLength);
+            return results;
+        }
+
+        for i in 0..proofs.len() {
+            let proof = proofs.get(i).unwrap();
+            let data = data_items.get(i).unwrap();
+
+            if data.len() > MAX_DATA_LEN {
+                Self::publish_error(&env, VerificationError::DataTooLarge);
+                results.push_back(false);
+                continue;
+            }
+
+            let message = Self::build_message(&env, &data, &salt);
+            let computed_hmac = Self::compute_hmac(&env, &message, &hmac_key);
+            let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+            Self::publish_result(&env, proof.as_ref(), is_valid, "data");
+            results.push_back(is_valid);
+        }
+
+        results
+    }
+
+    /// Locates the first failing proof in a batch.
+    ///
+    /// Stops at the first invalid item, so it is cheaper than checking the whole
+    /// batch when only the location of a problem matters.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proofs` - Vector of proofs to verify
+    /// * `data_items` - Vector of data items corresponding to each proof
+    /// * `salts` - Vector of salts corresponding to each proof
+    /// * `hmac_key` - The HMAC secret key (same for all proofs)
+    ///
+    /// # Returns
+    /// * `None` if every proof is valid
+    /// * `Some(index)` of the first invalid proof or, with `unique_salts` enabled,
+    ///   of the first item reusing an earlier salt
+    /// * `Some(INVALID_BATCH_INDEX)` if the input vectors differ in length
+    ///   (publishing `InvalidInput`) or the batch exceeds `batch_limits`
+    ///   (publishing `BatchTooLarge`); no item is checked
+    pub fn verify_batch_first_failure(
+        env: Env,
+        proofs: Vec<BytesN<32>>,
+        data_items: Vec<Bytes>,
+        salts: Vec<Bytes>,
+        hmac_key: BytesN<32>,
+    ) -> Option<u32> {
+        if !Self::check_batch_size(&env, &data_items) {
+            return Some(INVALID_BATCH_INDEX);
+        }
+
+        let count = proofs.len();
+
+        if count != data_items.len() || count != salts.len() {
+            Self::publish_error(&env, VerificationError::InvalidInput);
+            return Some(INVALID_BATCH_INDEX);
+        }
+
+        if Self::unique_salts(env.clone()) {
+            if let Some(index) = Self::find_reused_salt(&env, &salts) {
+                return Some(index);
+            }
+        }
+
+        for i in 0..count {
+            let proof = proofs.get(i).unwrap();
+            let data = data_items.get(i).unwrap();
+            let salt = salts.get(i).unwrap();
+
+            if !Self::verify_proof(env.clone(), proof, data, salt, hmac_key.clone()) {
+                return Some(i);
+            }
+        }
+
+        None
+    }
+
+    /// Performs structural validation of a Bulletproofs range proof on secp256k1.
+    ///
+    /// **What this checks:**
     /// 1. Commitment is a valid compressed secp256k1 point (0x02/0x03 prefix)
     /// 2. Proof points A, S, T1, T2 have valid compressed point prefixes
     /// 3. IPA proof length field is consistent with actual proof size
@@ -316,169 +4683,730 @@ impl ZkpVerifier {
         min: i64,
         max: i64,
     ) -> bool {
-        env.events().publish(
-            (Symbol::new(&env, "zk_range_verify_attempt"),),
-            (min, max),
-        );
+        env.events().publish(
+            (Symbol::new(&env, "zk_range_verify_attempt"),),
+            (min, max),
+        );
+
+        if min > max {
+            Self::publish_error(&env, VerificationError::InvalidRange);
+            return false;
+        }
+
+        // Minimum proof size: 4 compressed points (A,S,T1,T2 = 132) +
+        // 3 scalars (tau_x, mu, t_hat = 96) + 4 bytes IPA length + IPA data
+        let min_proof_len: u32 = 4 * 33 + 3 * 32 + 4;
+        if proof.len() < min_proof_len {
+            Self::publish_error(&env, VerificationError::InvalidProofLength);
+            return false;
+        }
+
+        // Validate commitment prefix (compressed EC point)
+        let commit_prefix = commitment.get(0).unwrap_or(0);
+        if commit_prefix != 0x02 && commit_prefix != 0x03 {
+            Self::publish_error(&env, VerificationError::InvalidCommitment);
+            return false;
+        }
+
+        // Validate that the four proof points (A, S, T1, T2) have valid prefixes
+        let point_offsets: [u32; 4] = [0, 33, 66, 99];
+        for offset in point_offsets {
+            let prefix = proof.get(offset).unwrap_or(0);
+            if prefix != 0x02 && prefix != 0x03 {
+                Self::publish_error(&env, VerificationError::InvalidProof);
+                return false;
+            }
+        }
+
+        // Validate IPA proof length field
+        let ipa_len_offset: u32 = 4 * 33 + 3 * 32;
+        let ipa_len = Self::read_u32_le(&proof, ipa_len_offset);
+        // Guard against overflow and unreasonably large IPA
+        if ipa_len > 10_000 || proof.len() < ipa_len_offset.saturating_add(4).saturating_add(ipa_len) {
+            Self::publish_error(&env, VerificationError::InvalidProofLength);
+            return false;
+        }
+
+        // Compute and emit a transcript binding hash for off-chain auditing.
+        // This hash ties the proof to this specific commitment and range, allowing
+        // off-chain verifiers to confirm the proof was not replayed from another context.
+        // Note: this is NOT a cryptographic verification -- full verification requires
+        // EC point arithmetic which is not available on Soroban for secp256k1.
+        let transcript_hash = Self::compute_transcript_binding(&env, &proof, &commitment, min, max);
+
+        env.events().publish(
+            (Symbol::new(&env, "zk_range_result"),),
+            true,
+        );
+        env.events().publish(
+            (Symbol::new(&env, "transcript_binding"),),
+            transcript_hash,
+        );
+
+        true
+    }
+
+    /// Verifies a Zero-Knowledge Age Proof.
+    /// 
+    /// Proves that a person is at least `min_age` years old without revealing
+    /// their exact age or birthdate.
+    /// 
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The ZK age proof bytes
+    /// * `commitment` - The commitment to the age value
+    /// * `min_age` - The minimum required age
+    /// 
+    /// # Returns
+    /// * `true` if the proof is valid and person is at least min_age
+    pub fn verify_zk_age_proof(
+        env: Env,
+        proof: Bytes,
+        commitment: BytesN<33>,
+        min_age: u32,
+    ) -> bool {
+        // Age proofs are range proofs with min=min_age, max=150
+        Self::verify_zk_range_proof(env, proof, commitment, min_age as i64, 150)
+    }
+
+    /// Verifies a Zero-Knowledge Balance Proof.
+    /// 
+    /// Proves that a balance is at least `required_amount` without revealing
+    /// the actual balance.
+    /// 
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `proof` - The ZK balance proof bytes
+    /// * `commitment` - The commitment to the balance value
+    /// * `required_amount` - The minimum required balance
+    /// 
+    /// # Returns
+    /// * `true` if the proof is valid and balance >= required_amount
+    pub fn verify_zk_balance_proof(
+        env: Env,
+        proof: Bytes,
+        commitment: BytesN<33>,
+        required_amount: i64,
+    ) -> bool {
+        // Balance proofs are range proofs with min=required_amount, max=i64::MAX
+        Self::verify_zk_range_proof(env, proof, commitment, required_amount, i64::MAX)
+    }
+
+    /// Computes a transcript binding hash that ties the proof to a specific commitment
+    /// and range. This mirrors the Fiat-Shamir transcript used during proof generation:
+    /// H(domain || V || n || A || S) where A, S are the first two proof points.
+    fn compute_transcript_binding(
+        env: &Env,
+        proof: &Bytes,
+        commitment: &BytesN<33>,
+        min: i64,
+        max: i64,
+    ) -> BytesN<32> {
+        let mut input = Bytes::new(env);
+
+        // Domain separator
+        let domain = b"Tessera_Bulletproofs_RangeProof";
+        for &byte in domain.iter() {
+            input.push_back(byte);
+        }
+
+        // Commitment V (33 bytes)
+        for i in 0..33u32 {
+            input.push_back(commitment.get(i).unwrap());
+        }
+
+        // Range bounds
+        for byte in min.to_be_bytes() {
+            input.push_back(byte);
+        }
+        for byte in max.to_be_bytes() {
+            input.push_back(byte);
+        }
+
+        // Proof points A (bytes 0..33) and S (bytes 33..66)
+        for i in 0..66u32 {
+            if let Some(b) = proof.get(i) {
+                input.push_back(b);
+            }
+        }
+
+        env.crypto().sha256(&input).into()
+    }
+
+    /// Applies `op` as `value op required`.
+    fn compare(op: CompareOp, value: i128, required: i128) -> bool {
+        match op {
+            CompareOp::Gte => value >= required,
+            CompareOp::Gt => value > required,
+            CompareOp::Lte => value <= required,
+            CompareOp::Lt => value < required,
+            CompareOp::Eq => value == required,
+        }
+    }
+
+    /// Overflow-checked addition of fixed-point amounts. All numeric verifiers go
+    /// through these helpers so they overflow (to `None`) at the same bounds.
+    fn checked_add_fp(a: i128, b: i128) -> Option<i128> {
+        a.checked_add(b)
+    }
+
+    /// Overflow-checked subtraction of fixed-point amounts.
+    fn checked_sub_fp(a: i128, b: i128) -> Option<i128> {
+        a.checked_sub(b)
+    }
+
+    /// Overflow-checked multiplication of a fixed-point amount by an integer factor.
+    fn checked_mul_fp(a: i128, b: i128) -> Option<i128> {
+        a.checked_mul(b)
+    }
+
+    /// Maps a standard base64 alphabet character to its 6-bit value.
+    fn base64_value(byte: u8) -> Option<u8> {
+        match byte {
+            b'A'..=b'Z' => Some(byte - b'A'),
+            b'a'..=b'z' => Some(byte - b'a' + 26),
+            b'0'..=b'9' => Some(byte - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    /// Publishes an `error` event carrying the numeric error code.
+    fn publish_error(env: &Env, error: VerificationError) {
+        Self::record_error(env, error);
+        env.events()
+            .publish((Symbol::new(env, "error"),), error as u32);
+    }
+
+    /// Publishes the `verification_result` event, counting a failed HMAC
+    /// comparison as `InvalidProof` in the error stats and appending the outcome
+    /// to the audit log. A valid proof also publishes `ProofVerified`.
+    fn publish_result(env: &Env, proof: &Bytes, is_valid: bool, kind: &str) {
+        let proof_id: BytesN<32> = env.crypto().sha256(proof).into();
+        if is_valid {
+            Self::publish_verified(env, &proof_id, kind);
+        } else {
+            Self::record_error(env, VerificationError::InvalidProof);
+        }
+        Self::append_audit_entry(env, proof_id, is_valid);
+        env.events().publish(
+            (Symbol::new(env, "verification_result"),),
+            is_valid,
+        );
+    }
+
+    /// Publishes the typed `ProofVerified` event for an authenticated proof.
+    fn publish_verified(env: &Env, proof_id: &BytesN<32>, kind: &str) {
+        ProofVerified {
+            kind: Symbol::new(env, kind),
+            proof_id: proof_id.clone(),
+            ledger: env.ledger().sequence(),
+        }
+        .publish(env);
+    }
+
+    /// Returns the audit log's `(length, head)`.
+    fn audit_log(env: &Env) -> (u32, BytesN<32>) {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AuditLog)
+            .unwrap_or_else(|| (0, BytesN::from_array(env, &[0u8; 32])))
+    }
+
+    /// Appends a verification outcome to the audit log: stores the entry at the
+    /// next index, advances the stored length and head, and publishes the entry
+    /// as `AuditLogged`.
+    fn append_audit_entry(env: &Env, proof_id: BytesN<32>, is_valid: bool) {
+        let (index, head) = Self::audit_log(env);
+        let entry = AuditEntry {
+            proof_id,
+            is_valid,
+            ledger: env.ledger().sequence(),
+        };
+        let head = audit_chain_hash(env, &head, &entry);
+
+        AuditLogged {
+            index,
+            proof_id: entry.proof_id.clone(),
+            is_valid,
+            ledger: entry.ledger,
+        }
+        .publish(env);
+
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::AuditEntry(index), &entry);
+        storage.set(&DataKey::AuditLog, &(index + 1, head));
+    }
+
+    /// Increments the per-variant failure counter returned by `get_error_stats`.
+    fn record_error(env: &Env, error: VerificationError) {
+        let mut stats = Self::get_error_stats(env.clone());
+        let count = stats.get(error as u32).unwrap_or(0);
+        stats.set(error as u32, count.saturating_add(1));
+        env.storage().instance().set(&DataKey::ErrorStats, &stats);
+    }
+
+    /// Checks the HMAC of a balance proof over `len(balance) || balance || len(salt) || salt`
+    /// (u32 big-endian lengths, after the domain prefix). The length prefixes pin
+    /// the balance/salt boundary, so bytes can't be moved between the two fields.
+    fn verify_balance_hmac(
+        env: &Env,
+        proof: &BytesN<32>,
+        balance_data: &Bytes,
+        salt: &Bytes,
+        hmac_key: &BytesN<32>,
+    ) -> bool {
+        if !Self::validate_inputs(env, balance_data, salt) {
+            return false;
+        }
+
+        let mut message = Self::domain_prefix(env);
+        Self::append_length_prefixed(&mut message, balance_data);
+        Self::append_length_prefixed(&mut message, salt);
+
+        let computed_hmac = Self::compute_hmac(env, &message, hmac_key);
+        let is_valid = Self::secure_compare(proof, &computed_hmac);
+
+        Self::publish_result(env, proof.as_ref(), is_valid, "balance");
+
+        is_valid
+    }
+
+    /// Checks the HMAC of an asset balance proof over
+    /// `[domain prefix] || asset_id || balance_data || salt`, with `namespace`'s
+    /// domain (the deployment's for `None`).
+    fn verify_asset_hmac(
+        env: &Env,
+        namespace: Option<&BytesN<32>>,
+        proof: &BytesN<32>,
+        asset_id: &BytesN<32>,
+        balance_data: &Bytes,
+        salt: &Bytes,
+        hmac_key: &BytesN<32>,
+    ) -> bool {
+        if !Self::check_balance_present(env, balance_data) {
+            return false;
+        }
+
+        if !Self::validate_inputs(env, balance_data, salt) {
+            return false;
+        }
+
+        let mut message = Self::scoped_domain_prefix(env, namespace);
+        message.append(&asset_id.clone().into());
+        message.append(balance_data);
+        message.append(salt);
+        let computed_hmac = Self::compute_hmac(env, &message, hmac_key);
+        let is_valid = Self::secure_compare(proof, &computed_hmac);
+
+        Self::publish_result(env, proof.as_ref(), is_valid, "asset_balance");
+
+        is_valid
+    }
+
+    /// Shared body of `verify_balance_against_threshold` and
+    /// `verify_balance_in_namespace`: checks an asset balance proof against the
+    /// decimals and threshold configured for the asset in `namespace` (contract-wide
+    /// for `None`), counting a namespaced success.
+    fn check_balance_threshold(
+        env: &Env,
+        namespace: Option<&BytesN<32>>,
+        proof: &BytesN<32>,
+        balance_data: &Bytes,
+        asset_id: &BytesN<32>,
+        salt: &Bytes,
+        hmac_key: &BytesN<32>,
+    ) -> bool {
+        let decimals = match Self::scoped_decimals(env, namespace, asset_id) {
+            Some(decimals) => decimals,
+            None => {
+                Self::publish_error(env, VerificationError::UnknownAsset);
+                return false;
+            }
+        };
+
+        let required = match Self::scoped_threshold(env, namespace, asset_id) {
+            Some(required) => required,
+            None => {
+                Self::publish_error(env, VerificationError::MissingThreshold);
+                return false;
+            }
+        };
+
+        if !Self::verify_asset_hmac(env, namespace, proof, asset_id, balance_data, salt, hmac_key) {
+            return false;
+        }
+
+        let balance_sufficient = match Self::parse_fixed_point(balance_data, decimals) {
+            Some(balance) => balance >= required,
+            None => {
+                Self::publish_error(env, VerificationError::InvalidInput);
+                false
+            }
+        };
+
+        env.events().publish(
+            (Symbol::new(env, "balance_check"),),
+            balance_sufficient,
+        );
+
+        if balance_sufficient {
+            Self::count_verification(env, namespace);
+        }
+        balance_sufficient
+    }
+
+    /// Opens a `verify_committed_sum` commitment, returning the committed value if
+    /// the tag matches `randomness` and the value is non-negative.
+    fn open_commitment(env: &Env, commitment: &Bytes, randomness: &Bytes) -> Option<i128> {
+        let mut pad_preimage = Bytes::from_array(env, &[0x00]);
+        pad_preimage.append(randomness);
+        let pad = env.crypto().sha256(&pad_preimage).to_array();
+
+        let mut value_bytes = [0u8; 16];
+        for (i, byte) in value_bytes.iter_mut().enumerate() {
+            *byte = commitment.get(i as u32).unwrap() ^ pad[i];
+        }
+
+        let mut tag_preimage = Bytes::from_array(env, &[0x01]);
+        tag_preimage.extend_from_array(&value_bytes);
+        tag_preimage.append(randomness);
+        let tag: BytesN<32> = env.crypto().sha256(&tag_preimage).into();
+        let expected: BytesN<32> = commitment.slice(16..COMMITMENT_LEN).try_into().ok()?;
+        if !Self::secure_compare(&tag, &expected) {
+            return None;
+        }
+
+        let value = i128::from_be_bytes(value_bytes);
+        (value >= 0).then_some(value)
+    }
+
+    /// Checks batch or aggregate items against `batch_limits`, publishing
+    /// `BatchTooLarge` if there are too many items or too many bytes in total.
+    fn check_batch_size(env: &Env, items: &Vec<Bytes>) -> bool {
+        let (max_items, max_total_bytes) = Self::batch_limits(env.clone());
+
+        // Check the count first so an oversized vector isn't walked
+        let mut within = items.len() <= max_items;
+        if within {
+            let mut total_bytes: u64 = 0;
+            for item in items.iter() {
+                total_bytes += item.len() as u64;
+            }
+            within = total_bytes <= max_total_bytes as u64;
+        }
+
+        if !within {
+            Self::publish_error(env, VerificationError::BatchTooLarge);
+        }
+        within
+    }
+
+    /// Verifies a proof like `verify_proof` unless the replay record under `key`
+    /// is still inside its window, then records the proof as consumed for
+    /// `REPLAY_WINDOW` ledgers (see `verify_proof_once`).
+    fn verify_and_consume(
+        env: &Env,
+        namespace: Option<&BytesN<32>>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+    ) -> bool {
+        let now = env.ledger().sequence();
+        let proof_id = Self::proof_id(env.clone(), proof.clone());
+        let key = match namespace {
+            Some(namespace) => {
+                DataKey::Namespaced(namespace.clone(), NamespaceKey::ConsumedProof(proof_id))
+            }
+            None => DataKey::ConsumedProof(proof_id),
+        };
 
-        if min > max {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidRange as u32,
-            );
+        let evict_at: Option<u32> = env.storage().persistent().get(&key);
+        if evict_at.is_some_and(|evict_at| now < evict_at) {
+            Self::publish_error(env, VerificationError::ProofReplayed);
             return false;
         }
 
-        // Minimum proof size: 4 compressed points (A,S,T1,T2 = 132) +
-        // 3 scalars (tau_x, mu, t_hat = 96) + 4 bytes IPA length + IPA data
-        let min_proof_len: u32 = 4 * 33 + 3 * 32 + 4;
-        if proof.len() < min_proof_len {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidProofLength as u32,
-            );
+        if !Self::verify_proof_with::<HmacSha256>(env.clone(), namespace, proof, data, salt, hmac_key, "data") {
             return false;
         }
 
-        // Validate commitment prefix (compressed EC point)
-        let commit_prefix = commitment.get(0).unwrap_or(0);
-        if commit_prefix != 0x02 && commit_prefix != 0x03 {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidCommitment as u32,
-            );
-            return false;
+        let storage = env.storage().persistent();
+        storage.set(&key, &now.saturating_add(REPLAY_WINDOW));
+        storage.extend_ttl(&key, REPLAY_WINDOW, REPLAY_WINDOW);
+
+        Self::count_verification(env, namespace);
+        true
+    }
+
+    /// Folds a Merkle path into a root (see `verify_rooted_membership`). Returns
+    /// `None` if `index` has bits set beyond the path's depth.
+    fn merkle_root(
+        env: &Env,
+        leaf: &Bytes,
+        path: &Vec<BytesN<32>>,
+        index: u32,
+    ) -> Option<BytesN<32>> {
+        if path.len() < 32 && index >> path.len() != 0 {
+            return None;
         }
 
-        // Validate that the four proof points (A, S, T1, T2) have valid prefixes
-        let point_offsets: [u32; 4] = [0, 33, 66, 99];
-        for offset in point_offsets {
-            let prefix = proof.get(offset).unwrap_or(0);
-            if prefix != 0x02 && prefix != 0x03 {
-                env.events().publish(
-                    (Symbol::new(&env, "error"),),
-                    VerificationError::InvalidProof as u32,
-                );
-                return false;
-            }
+        let mut node = Bytes::from_array(env, &[0x00]);
+        node.append(leaf);
+        let mut hash: BytesN<32> = env.crypto().sha256(&node).into();
+
+        for (level, sibling) in path.iter().enumerate() {
+            let (left, right) = if level < 32 && (index >> level) & 1 == 1 {
+                (sibling, hash)
+            } else {
+                (hash, sibling)
+            };
+            let mut node = Bytes::from_array(env, &[0x01]);
+            node.append(&left.into());
+            node.append(&right.into());
+            hash = env.crypto().sha256(&node).into();
         }
 
-        // Validate IPA proof length field
-        let ipa_len_offset: u32 = 4 * 33 + 3 * 32;
-        let ipa_len = Self::read_u32_le(&proof, ipa_len_offset);
-        // Guard against overflow and unreasonably large IPA
-        if ipa_len > 10_000 || proof.len() < ipa_len_offset.saturating_add(4).saturating_add(ipa_len) {
-            env.events().publish(
-                (Symbol::new(&env, "error"),),
-                VerificationError::InvalidProofLength as u32,
-            );
-            return false;
+        Some(hash)
+    }
+
+    /// Returns `submitter`'s rate-limit window as of the current ledger (reset if
+    /// it has elapsed), without storing it. Returns `None` if the stored window
+    /// starts after the current ledger.
+    fn current_rate_window(env: &Env, submitter: &Address) -> Option<RateWindow> {
+        let now = env.ledger().sequence();
+        let window: RateWindow = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RateLimit(submitter.clone()))
+            .unwrap_or(RateWindow { count: 0, window_start: now });
+
+        match ledger_gap(now, window.window_start)? {
+            gap if gap >= RATE_LIMIT_WINDOW => Some(RateWindow { count: 0, window_start: now }),
+            _ => Some(window),
         }
+    }
 
-        // Compute and emit a transcript binding hash for off-chain auditing.
-        // This hash ties the proof to this specific commitment and range, allowing
-        // off-chain verifiers to confirm the proof was not replayed from another context.
-        // Note: this is NOT a cryptographic verification -- full verification requires
-        // EC point arithmetic which is not available on Soroban for secp256k1.
-        let transcript_hash = Self::compute_transcript_binding(&env, &proof, &commitment, min, max);
+    /// Returns the index of the first salt that repeats an earlier one, publishing
+    /// a `DuplicateSalt` error if there is one.
+    fn find_reused_salt(env: &Env, salts: &Vec<Bytes>) -> Option<u32> {
+        let reused = (1..salts.len()).find(|&j| Self::salt_reused_at(salts, j));
+        if reused.is_some() {
+            Self::publish_error(env, VerificationError::DuplicateSalt);
+        }
+        reused
+    }
 
-        env.events().publish(
-            (Symbol::new(&env, "zk_range_result"),),
-            true,
-        );
-        env.events().publish(
-            (Symbol::new(&env, "transcript_binding"),),
-            transcript_hash,
-        );
+    /// Whether the salt at `index` repeats the salt of an earlier item.
+    fn salt_reused_at(salts: &Vec<Bytes>, index: u32) -> bool {
+        let salt = salts.get(index).unwrap();
+        (0..index).any(|i| salts.get(i).unwrap() == salt)
+    }
 
+    /// Rejects an empty balance field with `MissingBalance` (publishing the error)
+    /// so a missing value is never mistaken for a zero balance.
+    fn check_balance_present(env: &Env, balance_data: &Bytes) -> bool {
+        if balance_data.is_empty() {
+            Self::publish_error(env, VerificationError::MissingBalance);
+            return false;
+        }
         true
     }
 
-    /// Verifies a Zero-Knowledge Age Proof.
-    /// 
-    /// Proves that a person is at least `min_age` years old without revealing
-    /// their exact age or birthdate.
-    /// 
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    /// * `proof` - The ZK age proof bytes
-    /// * `commitment` - The commitment to the age value
-    /// * `min_age` - The minimum required age
-    /// 
-    /// # Returns
-    /// * `true` if the proof is valid and person is at least min_age
-    pub fn verify_zk_age_proof(
-        env: Env,
-        proof: Bytes,
-        commitment: BytesN<33>,
-        min_age: u32,
-    ) -> bool {
-        // Age proofs are range proofs with min=min_age, max=150
-        Self::verify_zk_range_proof(env, proof, commitment, min_age as i64, 150)
-    }
+    /// Checks the salt and data lengths accepted by every HMAC verifier.
+    fn check_inputs(data: &Bytes, salt: &Bytes) -> Result<(), VerificationError> {
+        if salt.len() < MIN_SALT_LEN {
+            return Err(VerificationError::InvalidSaltLength);
+        }
 
-    /// Verifies a Zero-Knowledge Balance Proof.
-    /// 
-    /// Proves that a balance is at least `required_amount` without revealing
-    /// the actual balance.
-    /// 
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    /// * `proof` - The ZK balance proof bytes
-    /// * `commitment` - The commitment to the balance value
-    /// * `required_amount` - The minimum required balance
-    /// 
-    /// # Returns
-    /// * `true` if the proof is valid and balance >= required_amount
-    pub fn verify_zk_balance_proof(
-        env: Env,
-        proof: Bytes,
-        commitment: BytesN<33>,
-        required_amount: i64,
-    ) -> bool {
-        // Balance proofs are range proofs with min=required_amount, max=i64::MAX
-        Self::verify_zk_range_proof(env, proof, commitment, required_amount, i64::MAX)
+        if data.len() > MAX_DATA_LEN {
+            return Err(VerificationError::DataTooLarge);
+        }
+
+        Ok(())
     }
 
-    /// Computes a transcript binding hash that ties the proof to a specific commitment
-    /// and range. This mirrors the Fiat-Shamir transcript used during proof generation:
-    /// H(domain || V || n || A || S) where A, S are the first two proof points.
-    fn compute_transcript_binding(
+    /// Checks a `verify_proof` proof and returns why it failed, or
+    /// `VerificationError::None` if it is valid. Invalid inputs are published as an
+    /// `error` event; a completed comparison goes through `publish_result` like
+    /// `verify_proof`.
+    fn proof_reason(
         env: &Env,
-        proof: &Bytes,
-        commitment: &BytesN<33>,
-        min: i64,
-        max: i64,
-    ) -> BytesN<32> {
-        let mut input = Bytes::new(env);
+        proof: &BytesN<32>,
+        data: &Bytes,
+        salt: &Bytes,
+        hmac_key: &BytesN<32>,
+    ) -> VerificationError {
+        if let Err(error) = Self::check_inputs(data, salt) {
+            Self::publish_error(env, error);
+            return error;
+        }
 
-        // Domain separator
-        let domain = b"Tessera_Bulletproofs_RangeProof";
-        for &byte in domain.iter() {
-            input.push_back(byte);
+        let message = Self::build_message(env, data, salt);
+        let computed_hmac = Self::compute_hmac(env, &message, hmac_key);
+        let is_valid = Self::secure_compare(proof, &computed_hmac);
+
+        Self::publish_result(env, proof.as_ref(), is_valid, "data");
+
+        if is_valid {
+            VerificationError::None
+        } else {
+            VerificationError::InvalidProof
         }
+    }
 
-        // Commitment V (33 bytes)
-        for i in 0..33u32 {
-            input.push_back(commitment.get(i).unwrap());
+    /// Like `check_inputs`, but publishes an `error` event and returns `false` on
+    /// the first violation.
+    fn validate_inputs(env: &Env, data: &Bytes, salt: &Bytes) -> bool {
+        match Self::check_inputs(data, salt) {
+            Ok(()) => true,
+            Err(error) => {
+                Self::publish_error(env, error);
+                false
+            }
         }
+    }
 
-        // Range bounds
-        for byte in min.to_be_bytes() {
-            input.push_back(byte);
+    /// Checks a variable-length MAC is exactly `expected` bytes, publishing
+    /// `InvalidProofLength` otherwise. Every entry point taking `proof: Bytes`
+    /// checks this before hashing, so a short proof can't reach an indexed read.
+    fn validate_mac_len(env: &Env, proof: &Bytes, expected: u32) -> bool {
+        if proof.len() != expected {
+            Self::publish_error(env, VerificationError::InvalidProofLength);
+            return false;
         }
-        for byte in max.to_be_bytes() {
-            input.push_back(byte);
+        true
+    }
+
+    /// Builds the HMAC message `[domain prefix] || data || salt` for `verify_proof`.
+    ///
+    /// Empty data is valid: the message is then just the salt, which the salt-length
+    /// check guarantees is non-empty, so HMAC is still computed over a well-defined input.
+    fn build_message(env: &Env, data: &Bytes, salt: &Bytes) -> Bytes {
+        Self::build_scoped_message(env, None, data, salt)
+    }
+
+    /// Builds the `verify_proof` message under `namespace`'s domain (the
+    /// deployment's for `None`).
+    fn build_scoped_message(env: &Env, namespace: Option<&BytesN<32>>, data: &Bytes, salt: &Bytes) -> Bytes {
+        Self::build_message_for_domain(env, &Self::scoped_domain(env, namespace), data, salt)
+    }
+
+    /// Builds the `verify_proof` message under an explicit `domain` rather than
+    /// the configured one; `self_test` uses it to check a fixed vector.
+    fn build_message_for_domain(env: &Env, domain: &Bytes, data: &Bytes, salt: &Bytes) -> Bytes {
+        let mut message = Self::prefix_for_domain(env, domain);
+        message.append(data);
+        message.append(salt);
+        message
+    }
+
+    /// Returns the `len(domain) || domain` prefix every HMAC message starts with,
+    /// or empty bytes when no domain is set.
+    fn domain_prefix(env: &Env) -> Bytes {
+        Self::scoped_domain_prefix(env, None)
+    }
+
+    /// Returns the domain prefix for `namespace`'s HMAC messages (the
+    /// deployment's for `None`).
+    fn scoped_domain_prefix(env: &Env, namespace: Option<&BytesN<32>>) -> Bytes {
+        Self::prefix_for_domain(env, &Self::scoped_domain(env, namespace))
+    }
+
+    /// Returns the `len(domain) || domain` prefix for `domain`, or empty bytes
+    /// when it is empty.
+    fn prefix_for_domain(env: &Env, domain: &Bytes) -> Bytes {
+        let mut prefix = Bytes::new(env);
+        if !domain.is_empty() {
+            Self::append_length_prefixed(&mut prefix, domain);
         }
+        prefix
+    }
 
-        // Proof points A (bytes 0..33) and S (bytes 33..66)
-        for i in 0..66u32 {
-            if let Some(b) = proof.get(i) {
-                input.push_back(b);
+    /// Appends `len(item) || item` to `message`, with the length as u32 big-endian.
+    fn append_length_prefixed(message: &mut Bytes, item: &Bytes) {
+        message.extend_from_array(&item.len().to_be_bytes());
+        message.append(item);
+    }
+
+    /// Serializes a `Statement` deterministically (see `verify_statement`).
+    fn serialize_statement(env: &Env, statement: &Statement) -> Bytes {
+        let mut encoded = Bytes::new(env);
+        Self::append_length_prefixed(&mut encoded, &statement.account.clone().to_xdr(env));
+        Self::append_length_prefixed(&mut encoded, &statement.asset.clone().into());
+        Self::append_length_prefixed(&mut encoded, &statement.balance);
+        Self::append_length_prefixed(
+            &mut encoded,
+            &Bytes::from_array(env, &statement.timestamp.to_be_bytes()),
+        );
+        encoded
+    }
+
+    /// Loads `namespace`'s HMAC key (the deployment's for `None`), panicking
+    /// with `NotInitialized` if it isn't set.
+    fn stored_key(env: &Env, namespace: Option<&BytesN<32>>) -> BytesN<32> {
+        let key = match namespace {
+            Some(namespace) => env
+                .storage()
+                .persistent()
+                .get(&DataKey::Namespaced(namespace.clone(), NamespaceKey::HmacKey)),
+            None => env.storage().instance().get(&DataKey::HmacKey),
+        };
+        key.unwrap_or_else(|| panic_with_error!(env, ContractError::NotInitialized))
+    }
+
+    /// Returns `namespace`'s domain, or the deployment domain for `None`.
+    fn scoped_domain(env: &Env, namespace: Option<&BytesN<32>>) -> Bytes {
+        match namespace {
+            Some(namespace) => env
+                .storage()
+                .persistent()
+                .get(&DataKey::Namespaced(namespace.clone(), NamespaceKey::Domain))
+                .unwrap_or_else(|| Bytes::new(env)),
+            None => Self::domain(env.clone()),
+        }
+    }
+
+    /// Returns the decimals configured for an asset in `namespace`, or
+    /// contract-wide for `None`.
+    fn scoped_decimals(env: &Env, namespace: Option<&BytesN<32>>, asset_id: &BytesN<32>) -> Option<u32> {
+        let key = match namespace {
+            Some(namespace) => {
+                DataKey::Namespaced(namespace.clone(), NamespaceKey::AssetDecimals(asset_id.clone()))
+            }
+            None => DataKey::AssetDecimals(asset_id.clone()),
+        };
+        env.storage().persistent().get(&key)
+    }
+
+    /// Returns the threshold configured for an asset in `namespace`, or
+    /// contract-wide for `None`.
+    fn scoped_threshold(env: &Env, namespace: Option<&BytesN<32>>, asset_id: &BytesN<32>) -> Option<i128> {
+        let key = match namespace {
+            Some(namespace) => {
+                DataKey::Namespaced(namespace.clone(), NamespaceKey::RequiredThreshold(asset_id.clone()))
             }
+            None => DataKey::RequiredThreshold(asset_id.clone()),
+        };
+        env.storage().persistent().get(&key)
+    }
+
+    /// Increments `namespace`'s `namespace_verified_count`; contract-wide
+    /// (`None`) verifications aren't counted.
+    fn count_verification(env: &Env, namespace: Option<&BytesN<32>>) {
+        if let Some(namespace) = namespace {
+            let key = DataKey::Namespaced(namespace.clone(), NamespaceKey::VerifiedCount);
+            let storage = env.storage().persistent();
+            let count: u64 = storage.get(&key).unwrap_or(0);
+            storage.set(&key, &count.saturating_add(1));
         }
+    }
 
-        env.crypto().sha256(&input).into()
+    /// Panics unless the stored admin has authorized the current invocation.
+    fn require_admin(env: &Env) {
+        Self::admin(env.clone()).require_auth();
     }
 
     /// Read a little-endian u32 from proof bytes at the given offset.
@@ -490,58 +5418,188 @@ impl ZkpVerifier {
         b0 | (b1 << 8) | (b2 << 16) | (b3 << 24)
     }
 
+    /// Verifies a proof like `verify_proof`, computing the expected tag with `M`
+    /// over `namespace`'s message layout (its domain; the deployment's for
+    /// `None`) and reporting success as a `ProofVerified` event of the given `kind`.
+    fn verify_proof_with<M: Mac>(
+        env: Env,
+        namespace: Option<&BytesN<32>>,
+        proof: BytesN<32>,
+        data: Bytes,
+        salt: Bytes,
+        hmac_key: BytesN<32>,
+        kind: &str,
+    ) -> bool {
+        // Log verification attempt (input lengths only)
+        if Self::debug_events_enabled(env.clone()) {
+            env.events().publish(
+                (Symbol::new(&env, "verify_attempt"),),
+                (data.len(), salt.len()),
+            );
+        }
+
+        // Validate input lengths
+        if !Self::validate_inputs(&env, &data, &salt) {
+            return false;
+        }
+
+        let message = Self::build_scoped_message(&env, namespace, &data, &salt);
+
+        // Compute the expected tag (HMAC-SHA256 for `verify_proof`)
+        let computed_hmac = M::compute(&env, &hmac_key, &message);
+
+        // Constant-time comparison to prevent timing attacks
+        let is_valid = Self::secure_compare(&proof, &computed_hmac);
+
+        // Log result
+        Self::publish_result(&env, proof.as_ref(), is_valid, kind);
+
+        is_valid
+    }
+
     /// Computes HMAC-SHA256 of a message with a given key.
     /// 
     /// # Implementation Note
     /// HMAC-SHA256 is computed as: HMAC(K, m) = H((K ⊕ opad) || H((K ⊕ ipad) || m))
     /// where H is SHA-256, opad = 0x5c repeated, ipad = 0x36 repeated
     fn compute_hmac(env: &Env, message: &Bytes, key: &BytesN<32>) -> BytesN<32> {
+        Self::compute_hmac_raw_key(env, message, &key.clone().into())
+    }
+
+    /// Computes HMAC-SHA256 with a key of any length (keys longer than the block
+    /// size are first hashed, per RFC 2104).
+    fn compute_hmac_raw_key(env: &Env, message: &Bytes, key: &Bytes) -> BytesN<32> {
         // HMAC constants
         const IPAD: u8 = 0x36;
         const OPAD: u8 = 0x5c;
         const BLOCK_SIZE: u32 = 64; // SHA-256 block size
 
-        // Create padded key (64 bytes)
-        let mut key_padded = Bytes::new(env);
-        for i in 0..32 {
-            key_padded.push_back(key.get(i).unwrap());
-        }
-        for _ in 32..BLOCK_SIZE {
-            key_padded.push_back(0);
+        let key = if key.len() > BLOCK_SIZE {
+            env.crypto().sha256(key).to_bytes().into()
+        } else {
+            key.clone()
+        };
+
+        // Build both padded keys in local buffers: XOR-ing in the contract avoids
+        // a host call per byte, which dominated the cost of every verification
+        let mut key_block = [0u8; BLOCK_SIZE as usize];
+        key.copy_into_slice(&mut key_block[..key.len() as usize]);
+        let mut inner_pad = [0u8; BLOCK_SIZE as usize];
+        let mut outer_pad = [0u8; BLOCK_SIZE as usize];
+        for (i, byte) in key_block.iter().enumerate() {
+            inner_pad[i] = byte ^ IPAD;
+            outer_pad[i] = byte ^ OPAD;
         }
 
         // Compute inner hash: H((K ⊕ ipad) || m)
-        let mut inner_data = Bytes::new(env);
-        for i in 0..BLOCK_SIZE {
-            inner_data.push_back(key_padded.get(i).unwrap() ^ IPAD);
-        }
+        let mut inner_data = Bytes::from_array(env, &inner_pad);
         inner_data.append(message);
-        
+
         let inner_bn: BytesN<32> = env.crypto().sha256(&inner_data).into();
 
         // Compute outer hash: H((K ⊕ opad) || inner_hash)
-        let mut outer_data = Bytes::new(env);
-        for i in 0..BLOCK_SIZE {
-            outer_data.push_back(key_padded.get(i).unwrap() ^ OPAD);
+        let mut outer_data = Bytes::from_array(env, &outer_pad);
+        outer_data.append(inner_bn.as_ref());
+
+        env.crypto().sha256(&outer_data).into()
+    }
+
+    /// Computes the HMAC of a message with the given hash algorithm.
+    fn compute_hmac_alg(env: &Env, message: &Bytes, key: &BytesN<32>, alg: HashAlg) -> Bytes {
+        match alg {
+            HashAlg::Sha256 => Self::compute_hmac(env, message, key).into(),
+            HashAlg::Sha512 => Bytes::from_array(env, &Self::compute_hmac_sha512(message, key)),
+            HashAlg::Keccak256 => Self::compute_hmac_keccak256(env, message, key).into(),
         }
-        for i in 0..32u32 {
-            outer_data.push_back(inner_bn.get(i).unwrap());
+    }
+
+    /// Computes HMAC-Keccak256 of a message with a given key, as produced by
+    /// Ethereum tooling (original Keccak padding, not SHA3-256).
+    ///
+    /// The block size is the Keccak-256 rate of 136 bytes, so the 32-byte key is
+    /// zero-padded to 136 bytes before the ipad/opad XOR.
+    fn compute_hmac_keccak256(env: &Env, message: &Bytes, key: &BytesN<32>) -> BytesN<32> {
+        const IPAD: u8 = 0x36;
+        const OPAD: u8 = 0x5c;
+        const BLOCK_SIZE: usize = 136; // Keccak-256 rate
+
+        let mut key_padded = [0u8; BLOCK_SIZE];
+        key_padded[..32].copy_from_slice(&key.to_array());
+
+        let mut inner_data = Bytes::from_array(env, &key_padded.map(|b| b ^ IPAD));
+        inner_data.append(message);
+        let inner_hash = env.crypto().keccak256(&inner_data);
+
+        let mut outer_data = Bytes::from_array(env, &key_padded.map(|b| b ^ OPAD));
+        outer_data.append(&inner_hash.to_bytes().into());
+
+        env.crypto().keccak256(&outer_data).into()
+    }
+
+    /// Computes HMAC-SHA512 of a message with a given key.
+    ///
+    /// Soroban has no SHA-512 host function, so this runs in-contract and costs
+    /// noticeably more instructions than HMAC-SHA256.
+    fn compute_hmac_sha512(message: &Bytes, key: &BytesN<32>) -> [u8; 64] {
+        use sha2::{Digest, Sha512};
+
+        const IPAD: u8 = 0x36;
+        const OPAD: u8 = 0x5c;
+        const BLOCK_SIZE: usize = 128; // SHA-512 block size
+
+        // Create padded key (128 bytes)
+        let mut key_padded = [0u8; BLOCK_SIZE];
+        key_padded[..32].copy_from_slice(&key.to_array());
+
+        // Compute inner hash: H((K ⊕ ipad) || m)
+        let mut inner = Sha512::new();
+        inner.update(key_padded.map(|b| b ^ IPAD));
+        let mut chunk = [0u8; 256];
+        let mut offset = 0;
+        while offset < message.len() {
+            let end = (offset + chunk.len() as u32).min(message.len());
+            let part = &mut chunk[..(end - offset) as usize];
+            message.slice(offset..end).copy_into_slice(part);
+            inner.update(&*part);
+            offset = end;
         }
+        let inner_hash = inner.finalize();
 
-        env.crypto().sha256(&outer_data).into()
+        // Compute outer hash: H((K ⊕ opad) || inner_hash)
+        let mut outer = Sha512::new();
+        outer.update(key_padded.map(|b| b ^ OPAD));
+        outer.update(inner_hash);
+
+        outer.finalize().into()
     }
 
     /// Performs constant-time comparison of two 32-byte hashes.
     /// This prevents timing attacks during proof verification.
     fn secure_compare(a: &BytesN<32>, b: &BytesN<32>) -> bool {
+        Self::secure_compare_bytes(a.as_ref(), b.as_ref())
+    }
+
+    /// Performs constant-time comparison of two variable-length byte strings.
+    ///
+    /// A length mismatch returns early; lengths are not secret. For equal-length
+    /// inputs every byte is XOR-accumulated without an early exit, and the
+    /// accumulator is passed through `black_box` so the loop can't be turned into
+    /// a short-circuiting comparison.
+    fn secure_compare_bytes(a: &Bytes, b: &Bytes) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+
         let mut diff: u32 = 0;
-        
-        for i in 0..32 {
+
+        for i in 0..a.len() {
             let byte_a = a.get(i).unwrap();
             let byte_b = b.get(i).unwrap();
-            diff |= (byte_a ^ byte_b) as u32;
+            diff = core::hint::black_box(diff | (byte_a ^ byte_b) as u32);
         }
-        
+
         diff == 0
     }
-}
\ No newline at end of file
+}
+
+mod test;
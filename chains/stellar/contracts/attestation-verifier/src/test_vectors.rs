@@ -0,0 +1,88 @@
+//! Published conformance vectors for `verify_proof`.
+//!
+//! Each vector was produced by the contract's canonical HMAC-SHA256 layout:
+//! `proof = HMAC-SHA256(key, data || salt)`. External tooling should reproduce
+//! every `expected_proof` byte-for-byte before submitting proofs on-chain.
+//!
+//! `PREFIX_MAC_BALANCE_SALT_16` covers `MacMode::PrefixMac` over the same input
+//! as `BALANCE_SALT_16`: `proof = SHA-256(key || data || salt)`.
+
+/// A single `(data, salt, key, expected_proof)` conformance vector.
+pub struct TestVector {
+    /// The proven data
+    pub data: &'static [u8],
+    /// The salt appended to the data
+    pub salt: &'static [u8],
+    /// The 32-byte HMAC key
+    pub key: [u8; 32],
+    /// The expected 32-byte proof
+    pub expected_proof: [u8; 32],
+}
+
+/// HMAC key shared by all published vectors.
+pub const KEY: [u8; 32] = [
+    0x55, 0x75, 0x43, 0x32, 0xf6, 0x05, 0xd5, 0x14, 0xb1, 0x65, 0x8c, 0x16, 0x2f, 0x87, 0x86, 0xf7,
+    0x79, 0xb4, 0x24, 0xa7, 0x4e, 0xf4, 0xa6, 0xd7, 0x42, 0x7d, 0x26, 0x86, 0x0f, 0x84, 0x5c, 0x77,
+];
+
+/// 16-byte salt `00 01 .. 0f` (the minimum accepted length).
+pub const SALT_16: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+
+/// 32-byte salt `00 01 .. 1f`.
+pub const SALT_32: [u8; 32] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+/// Empty data with a 16-byte salt.
+pub const EMPTY_DATA: TestVector = TestVector {
+    data: b"",
+    salt: &SALT_16,
+    key: KEY,
+    expected_proof: [
+        0x14, 0x25, 0x39, 0xcb, 0xf9, 0x59, 0xcd, 0xf1, 0x0a, 0xee, 0x5f, 0x66, 0xe4, 0xaa, 0x88,
+        0x24, 0x76, 0xec, 0xec, 0xc2, 0xda, 0x58, 0x87, 0x25, 0xf6, 0x78, 0x7d, 0xc5, 0xbc, 0xbf,
+        0x38, 0xd0,
+    ],
+};
+
+/// Decimal balance string `"1000.0"` with a 16-byte salt.
+pub const BALANCE_SALT_16: TestVector = TestVector {
+    data: b"1000.0",
+    salt: &SALT_16,
+    key: KEY,
+    expected_proof: [
+        0x73, 0x49, 0x97, 0x3a, 0xb7, 0xfb, 0xbb, 0xf9, 0x0f, 0x64, 0x9a, 0xfd, 0xfa, 0xea, 0x00,
+        0x77, 0x0e, 0x9a, 0x02, 0x51, 0xaa, 0x8a, 0xfa, 0xca, 0x32, 0x59, 0x8c, 0xb7, 0x96, 0xe8,
+        0x4b, 0x8c,
+    ],
+};
+
+/// Raw bytes `01 02 03 04 05` with a 32-byte salt.
+pub const BYTES_SALT_32: TestVector = TestVector {
+    data: &[0x01, 0x02, 0x03, 0x04, 0x05],
+    salt: &SALT_32,
+    key: KEY,
+    expected_proof: [
+        0xe0, 0x7d, 0x88, 0xff, 0x77, 0x23, 0x68, 0x59, 0x3e, 0xb9, 0x61, 0x13, 0xbe, 0xef, 0xe9,
+        0xe1, 0x47, 0x1c, 0x4a, 0xca, 0x9f, 0x05, 0xc3, 0xad, 0x6c, 0x35, 0x4a, 0xf8, 0xb1, 0x1b,
+        0xae, 0x76,
+    ],
+};
+
+/// All published vectors.
+pub const VECTORS: [TestVector; 3] = [EMPTY_DATA, BALANCE_SALT_16, BYTES_SALT_32];
+
+/// `MacMode::PrefixMac` over the same input as `BALANCE_SALT_16`.
+pub const PREFIX_MAC_BALANCE_SALT_16: TestVector = TestVector {
+    data: b"1000.0",
+    salt: &SALT_16,
+    key: KEY,
+    expected_proof: [
+        0x31, 0x15, 0x18, 0x9b, 0x8a, 0x17, 0xd5, 0x5d, 0xe8, 0x20, 0xeb, 0x9e, 0xc7, 0x87, 0x3a,
+        0x1c, 0xf6, 0x0d, 0xfb, 0x51, 0x9c, 0xac, 0x3e, 0xf4, 0xe0, 0xe1, 0x07, 0x14, 0x4f, 0x67,
+        0xd9, 0xeb,
+    ],
+};